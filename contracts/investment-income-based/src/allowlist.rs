@@ -0,0 +1,41 @@
+use soroban_sdk::{contractclient, contracttype, Address, Env};
+
+use crate::storage as Storage;
+
+/// Interface an external verifier contract (e.g. a KYC registry) must expose
+#[contractclient(name = "InvestorVerifierClient")]
+pub trait InvestorVerifier {
+    fn is_verified(env: Env, investor: Address) -> bool;
+}
+
+#[derive(Default)]
+#[contracttype]
+pub struct AllowlistConfig {
+    pub enabled: bool,
+    pub verifier: Option<Address>,
+}
+
+/// Returns whether `addr` may hold positions.
+///
+/// Every address is allowed while the allowlist is disabled. Otherwise the address must be in the
+/// managed allowlist or be verified by the external verifier, if one is configured. A verifier call
+/// that fails counts as not verified.
+pub fn is_allowed(env: &Env, addr: &Address) -> bool {
+    !Storage::get_allowlist_config(env).enabled || is_listed(env, addr)
+}
+
+/// Returns whether `addr` is in the managed allowlist or verified by the external verifier,
+/// whether or not the allowlist is enforced.
+pub fn is_listed(env: &Env, addr: &Address) -> bool {
+    if Storage::is_allowlisted(env, addr) {
+        return true;
+    }
+
+    match Storage::get_allowlist_config(env).verifier {
+        Some(verifier) => matches!(
+            InvestorVerifierClient::new(env, &verifier).try_is_verified(addr),
+            Ok(Ok(true))
+        ),
+        None => false,
+    }
+}
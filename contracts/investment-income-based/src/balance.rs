@@ -1,10 +1,13 @@
 use soroban_sdk::{contractevent, contracttype, Env};
 use stellar_contract_utils::math::wad::Wad;
 
+use crate::data::SweepTarget;
+
 const LOWER_AMOUNT_FOR_COMMISSION_REDUCTION: i128 = 100;
 const LOWER_DIVISOR: u32 = 10;
 const UPPER_DIVISOR: u32 = 60;
 const AMOUNT_PER_COMMISSION_REDUCTION: i128 = 400;
+pub const BPS_DENOMINATOR: i128 = 10_000;
 
 pub fn calculate_rate_denominator(amount: &i128, decimals: u32) -> u32 {
     let scale_factor = 10_i128.pow(decimals);
@@ -23,6 +26,22 @@ pub fn calculate_rate_denominator(amount: &i128, decimals: u32) -> u32 {
     LOWER_DIVISOR + a as u32
 }
 
+/// Calculates the penalty interest accrued by an overdue amount
+pub fn calculate_late_penalty(overdue_amount: i128, penalty_bps: u32) -> i128 {
+    overdue_amount * penalty_bps as i128 / BPS_DENOMINATOR
+}
+
+/// Calculates the referral reward credited out of an investment's commission
+pub fn calculate_referral_reward(commission: i128, share_bps: u32) -> i128 {
+    commission * share_bps as i128 / BPS_DENOMINATOR
+}
+
+/// Calculates the keeper bounty for a paid amount, limited by the available commission
+pub fn calculate_keeper_bounty(amount_paid: i128, bounty_bps: u32, commission: i128) -> i128 {
+    let bounty = amount_paid * bounty_bps as i128 / BPS_DENOMINATOR;
+    bounty.min(commission).max(0)
+}
+
 #[contracttype]
 pub struct ContractBalance {
     pub reserve: i128,
@@ -33,6 +52,25 @@ pub struct ContractBalance {
     pub reserve_contributions: i128,
     pub project_withdrawals: i128,
     pub moved_from_project_to_reserve: i128,
+    pub keeper_bounties: i128,
+    pub recovery: i128,
+    pub recovery_payments: i128,
+    pub penalties_paid: i128,
+    pub referrals: i128,
+    pub referrals_paid: i128,
+    pub revenue: i128,
+    pub revenue_payments: i128,
+    pub distributions: i128,
+    pub distributions_paid: i128,
+}
+
+/// Comparison between the tokens held by the contract and the balances it accounts for
+#[contracttype]
+pub struct Reconciliation {
+    pub holdings: i128,
+    pub booked: i128,
+    pub surplus: i128,
+    pub deficit: i128,
 }
 
 #[contractevent(topics = ["CBUPDATED"])]
@@ -45,6 +83,16 @@ pub struct ContractBalanceUpdated {
     pub reserve_contributions: i128,
     pub project_withdrawals: i128,
     pub moved_from_project_to_reserve: i128,
+    pub keeper_bounties: i128,
+    pub recovery: i128,
+    pub recovery_payments: i128,
+    pub penalties_paid: i128,
+    pub referrals: i128,
+    pub referrals_paid: i128,
+    pub revenue: i128,
+    pub revenue_payments: i128,
+    pub distributions: i128,
+    pub distributions_paid: i128,
 }
 
 impl Default for ContractBalance {
@@ -64,11 +112,27 @@ impl ContractBalance {
             reserve_contributions: 0_i128,
             project_withdrawals: 0_i128,
             moved_from_project_to_reserve: 0_i128,
+            keeper_bounties: 0_i128,
+            recovery: 0_i128,
+            recovery_payments: 0_i128,
+            penalties_paid: 0_i128,
+            referrals: 0_i128,
+            referrals_paid: 0_i128,
+            revenue: 0_i128,
+            revenue_payments: 0_i128,
+            distributions: 0_i128,
+            distributions_paid: 0_i128,
         }
     }
 
     pub fn sum(&self) -> i128 {
-        self.comission + self.project + self.reserve
+        self.comission
+            + self.project
+            + self.reserve
+            + self.recovery
+            + self.referrals
+            + self.revenue
+            + self.distributions
     }
 
     pub fn recalculate_from_investment(&mut self, amounts: &Amount) {
@@ -93,12 +157,103 @@ impl ContractBalance {
         self.payments += amount;
     }
 
+    /// Tracks the part of an investor payment that was late-payment penalty
+    pub fn recalculate_from_penalty_payment(&mut self, penalty: &i128) {
+        self.penalties_paid += penalty;
+    }
+
     pub fn recalculate_from_project_to_reserver_movement(&mut self, amount: &i128) {
         self.project -= amount;
         self.reserve += amount;
         self.moved_from_project_to_reserve += amount;
     }
 
+    /// Moves what the reserve is missing to cover `amount_needed` from the project balance, limited by
+    /// the project balance and by `cap` (0 means no limit). Returns the amount moved.
+    pub fn top_up_reserve_from_project(&mut self, amount_needed: i128, cap: i128) -> i128 {
+        let mut amount = (amount_needed - self.reserve).min(self.project);
+        if cap > 0 {
+            amount = amount.min(cap);
+        }
+
+        if amount <= 0 {
+            return 0;
+        }
+
+        self.recalculate_from_project_to_reserver_movement(&amount);
+        amount
+    }
+
+    /// Compares the tokens held by the contract with every balance it accounts for
+    pub fn reconcile(&self, holdings: i128) -> Reconciliation {
+        let booked = self.sum();
+        Reconciliation {
+            holdings,
+            booked,
+            surplus: (holdings - booked).max(0),
+            deficit: (booked - holdings).max(0),
+        }
+    }
+
+    /// Adds a swept surplus to the target balance. Surplus swept out to the owner leaves no trace in the balances.
+    pub fn recalculate_from_surplus_sweep(&mut self, target: &SweepTarget, amount: &i128) {
+        match target {
+            SweepTarget::Reserve => self.reserve += amount,
+            SweepTarget::Project => self.project += amount,
+            SweepTarget::Commission => self.comission += amount,
+            SweepTarget::Owner => {}
+        }
+    }
+
+    pub fn recalculate_from_keeper_bounty(&mut self, amount: &i128) {
+        self.comission -= amount;
+        self.keeper_bounties += amount;
+    }
+
+    /// Moves a referral reward from the commission to the referral liabilities
+    pub fn recalculate_from_referral_credit(&mut self, amount: &i128) {
+        self.comission -= amount;
+        self.referrals += amount;
+    }
+
+    pub fn recalculate_from_referral_payment(&mut self, amount: &i128) {
+        self.referrals -= amount;
+        self.referrals_paid += amount;
+    }
+
+    pub fn recalculate_from_revenue_report(&mut self, amount: &i128) {
+        self.revenue += amount;
+    }
+
+    pub fn recalculate_from_revenue_payment(&mut self, amount: &i128) {
+        self.revenue -= amount;
+        self.revenue_payments += amount;
+    }
+
+    pub fn recalculate_from_distribution(&mut self, amount: &i128) {
+        self.distributions += amount;
+    }
+
+    pub fn recalculate_from_distribution_payment(&mut self, amount: &i128) {
+        self.distributions -= amount;
+        self.distributions_paid += amount;
+    }
+
+    pub fn recalculate_from_default(&mut self) {
+        self.recovery += self.reserve + self.project;
+        self.reserve = 0;
+        self.project = 0;
+    }
+
+    pub fn recalculate_from_recovery_deposit(&mut self, amount: &i128) {
+        self.recovery += amount;
+    }
+
+    pub fn recalculate_from_recovery_payment(&mut self, amount: &i128) {
+        self.recovery -= amount;
+        self.recovery_payments += amount;
+    }
+
     /// Emits a ContractBalancesUpdated event
     pub fn emit_event(&self, env: &Env) {
         ContractBalanceUpdated {
@@ -110,6 +265,16 @@ impl ContractBalance {
             reserve_contributions: self.reserve_contributions,
             project_withdrawals: self.project_withdrawals,
             moved_from_project_to_reserve: self.moved_from_project_to_reserve,
+            keeper_bounties: self.keeper_bounties,
+            recovery: self.recovery,
+            recovery_payments: self.recovery_payments,
+            penalties_paid: self.penalties_paid,
+            referrals: self.referrals,
+            referrals_paid: self.referrals_paid,
+            revenue: self.revenue,
+            revenue_payments: self.revenue_payments,
+            distributions: self.distributions,
+            distributions_paid: self.distributions_paid,
         }
         .publish(env);
     }
@@ -0,0 +1,35 @@
+use soroban_sdk::{contracttype, Env};
+
+/// Bonus basis points added to the interest rate of investments matching a campaign incentive
+#[derive(Default)]
+#[contracttype]
+pub struct BonusSchedule {
+    pub early_bird_bps: u32,
+    pub early_bird_raised: i128,
+    pub early_bird_end_ts: u64,
+    pub volume_bonus_bps: u32,
+    pub volume_threshold: i128,
+}
+
+impl BonusSchedule {
+    /// Returns the bonus basis points of an investment of `amount` made when `raised_so_far` has been raised.
+    ///
+    /// The early-bird bonus applies while less than `early_bird_raised` has been raised or before
+    /// `early_bird_end_ts` (a zero value disables either condition). The volume bonus applies to
+    /// investments of at least `volume_threshold`. Both bonuses add up.
+    pub fn bonus_bps(&self, env: &Env, raised_so_far: i128, amount: i128) -> u32 {
+        let mut bonus: u32 = 0;
+
+        let early_by_amount = self.early_bird_raised > 0 && raised_so_far < self.early_bird_raised;
+        let early_by_time = self.early_bird_end_ts > 0 && env.ledger().timestamp() < self.early_bird_end_ts;
+        if early_by_amount || early_by_time {
+            bonus += self.early_bird_bps;
+        }
+
+        if self.volume_bonus_bps > 0 && amount >= self.volume_threshold {
+            bonus += self.volume_bonus_bps;
+        }
+
+        bonus
+    }
+}
@@ -21,14 +21,14 @@ pub fn calculate_next_claim(e: &Env, investment: &Investment) -> Claim {
             lts if lts > 0 => lts + SECONDS_IN_MONTH,
             _ => e.ledger().timestamp() + SECONDS_IN_MONTH,
         },
-        amount_to_pay: investment.regular_payment,
+        amount_to_pay: investment.regular_payment + investment.arrears,
     }
 }
 
 
-pub fn calculate_claimable_payments(env: &Env, investment: &Investment, return_months: u32) -> u32 {
+pub fn calculate_claimable_payments(env: &Env, investment: &Investment) -> u32 {
     let now = env.ledger().timestamp();
-    let remaining = return_months - investment.payments_transferred;
+    let remaining = investment.return_months - investment.payments_transferred;
 
     let eligible = if investment.last_transfer_ts == 0 {
         let elapsed = now - investment.claimable_ts;
@@ -2,3 +2,21 @@
 pub const SECONDS_IN_DAY: u64 = 86400;
 pub const SECONDS_IN_WEEK: u64 = 7 * SECONDS_IN_DAY;
 pub const SECONDS_IN_MONTH: u64 = 30 * SECONDS_IN_DAY;
+
+// Delay before a proposed project address change can be applied
+pub const PROJECT_ADDRESS_CHANGE_DELAY: u64 = SECONDS_IN_WEEK;
+
+// Maximum number of periods covered by an obligations forecast
+pub const MAX_FORECAST_PERIODS: u64 = 120;
+
+// Keeper incentives in basis points
+pub const MAX_KEEPER_BOUNTY_BPS: u32 = 1_000;
+
+// Late-payment penalty per period in basis points
+pub const MAX_LATE_PENALTY_BPS: u32 = 1_000;
+
+// Longest grace window before arrears accrue late-payment penalty, in days
+pub const MAX_LATE_GRACE_DAYS: u64 = 365;
+
+// Combined early-bird and volume bonus in basis points
+pub const MAX_BONUS_BPS: u32 = 1_000;
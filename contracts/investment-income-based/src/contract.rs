@@ -1,14 +1,33 @@
 use soroban_sdk::token::TokenClient;
-use soroban_sdk::{contract, contractimpl, token, Address, Env, Map, String};
-use stellar_access::ownable::{self as ownable};
+use soroban_sdk::{contract, contractimpl, panic_with_error, token, Address, Env, Map, String, Vec};
+use stellar_access::ownable::{self as ownable, Ownable};
 use stellar_contract_utils::pausable::{self as pausable, Pausable};
 use stellar_macros::{only_owner, when_not_paused};
 use stellar_tokens::non_fungible::{Base, NonFungibleToken};
 
-use crate::balance::{Amount, CalculateAmounts, ContractBalance};
+use crate::allowlist::{self, AllowlistConfig};
+use crate::balance::{calculate_keeper_bounty, calculate_referral_reward, Amount, CalculateAmounts, ContractBalance, Reconciliation};
+use crate::bonus::BonusSchedule;
 use crate::claim::{calculate_claimable_payments, Claim};
-use crate::data::{ContractData, FromNumber, InvestmentContractParams, State};
+use crate::constants::PROJECT_ADDRESS_CHANGE_DELAY;
+use crate::data::{
+    ContractData, ContractParameter, FromNumber, InvestmentContractParams, Operation, PendingProjectAddress,
+    RoundParams, State, SweepTarget,
+};
+use crate::distribution::Distribution;
+use crate::events::{
+    self, AllowlistConfigUpdated, AllowlistUpdated, ClaimDelegateUpdated, Distributed, DistributionClaimed,
+    ComplianceOfficerUpdated, InvestmentCreated, KeeperRewarded, OperationPauseUpdated, ParameterUpdated,
+    PaymentShortfall, PaymentTrigger, PayoutAddressUpdated, PositionFreezeUpdated, PositionSeized,
+    ProjectAddressChangeProposed, ProjectAddressUpdated, ProjectWithdrawal, RecoveryClaimed, RecoveryDeposited, ReferralCredited,
+    ReferralRewardsClaimed, ReserveAutoTopUp, RevenueReported, RoundOpened,
+    ReserveContribution, ReserveMove, SurplusSwept, TokenRescued,
+};
+use crate::forecast::{self, ObligationsForecast};
 use crate::investment::{Investment, InvestmentReturnType};
+use crate::payment::{self, BatchPaymentSummary};
+use crate::recovery::{self, RecoveryPool};
+use crate::revenue::RevenuePool;
 use crate::validation::{self, Error};
 
 use crate::{require, storage as Storage};
@@ -17,6 +36,210 @@ fn get_token<'a>(env: &'a Env, contract_data: &ContractData) -> TokenClient<'a>
     token::Client::new(env, &contract_data.token)
 }
 
+fn enforce_compliance_auth(env: &Env, caller: &Address) -> Result<(), Error> {
+    caller.require_auth();
+    let is_owner = ownable::get_owner(env).as_ref() == Some(caller);
+    let is_officer = Storage::get_compliance_officer(env).as_ref() == Some(caller);
+    require!(is_owner || is_officer, Error::CallerNotComplianceOfficer);
+    Ok(())
+}
+
+/// Applies the automatic top-up policy when the reserve can't cover `amount_needed`,
+/// moving the missing amount from the project balance
+fn auto_top_up_reserve(env: &Env, contract_data: &ContractData, contract_balances: &mut ContractBalance, amount_needed: i128) {
+    if !contract_data.auto_top_up || amount_needed <= contract_balances.reserve {
+        return;
+    }
+
+    let amount = contract_balances.top_up_reserve_from_project(amount_needed, contract_data.auto_top_up_cap);
+    if amount > 0 {
+        ReserveAutoTopUp { amount, amount_needed }.publish(env);
+    }
+}
+
+/// Moves up to `amount` of the invested total of `from` to `to`, keeping the investors count in sync
+fn move_investor_total(env: &Env, from: &Address, to: &Address, amount: i128) {
+    let from_total = Storage::get_investor_total(env, from);
+    let to_total = Storage::get_investor_total(env, to);
+    let moved = amount.min(from_total);
+    if moved <= 0 || from == to {
+        return;
+    }
+
+    let mut investors_count = Storage::get_investors_count(env);
+    if from_total == moved {
+        investors_count -= 1;
+    }
+    if to_total == 0 {
+        investors_count += 1;
+    }
+    Storage::update_investors_count(env, investors_count);
+    Storage::update_investor_total(env, from, from_total - moved);
+    Storage::update_investor_total(env, to, to_total + moved);
+}
+
+/// Moves the invested total of position `token_id` from `from` to `to`, so it counts towards the caps of its new holder
+fn move_position_total(env: &Env, from: &Address, to: &Address, token_id: u32) {
+    if let Some(investment) = Storage::get_investment(env, token_id) {
+        move_investor_total(env, from, to, investment.deposited + investment.commission);
+    }
+}
+
+/// Stores the updated campaign parameters and emits a ParameterUpdated event
+fn update_parameter(env: &Env, contract_data: &ContractData, parameter: ContractParameter, old_value: i128, new_value: i128) -> Result<(), Error> {
+    validation::validate_contract_params(contract_data)?;
+    Storage::update_contract_data(env, contract_data);
+
+    ParameterUpdated {
+        parameter,
+        old_value,
+        new_value,
+    }
+    .publish(env);
+    Ok(())
+}
+
+fn pay_due_investments(
+    env: &Env,
+    token_ids: &Vec<u32>,
+    next_cursor: Option<u32>,
+) -> Result<BatchPaymentSummary, Error> {
+    validation::validate_operation_not_paused(env, Operation::Payouts)?;
+    let contract_data = Storage::get_contract_data(env);
+    validation::validate_not_defaulted(&contract_data)?;
+    let (mut due_payments, skipped) = payment::collect_due_payments(env, &contract_data, token_ids);
+
+    let mut contract_balances: ContractBalance = Storage::get_balances_or_new(env);
+    auto_top_up_reserve(env, &contract_data, &mut contract_balances, payment::total_due(&due_payments));
+    if payment::total_due(&due_payments) > contract_balances.reserve {
+        require!(contract_data.shortfall_mode, Error::ContractInsufficientBalance);
+        due_payments = payment::apply_shortfall(env, &due_payments, contract_balances.reserve);
+    }
+    let total_paid = payment::total_due(&due_payments);
+
+    let tk = get_token(env, &contract_data);
+    let mut paid: Vec<u32> = Vec::new(env);
+    for (token_id, due) in due_payments.iter() {
+        if due.amount > 0 {
+            tk.try_transfer(&env.current_contract_address(), &due.recipient, &due.amount)
+                .map_err(|_| Error::RecipientCannotReceivePayment)?
+                .map_err(|_| Error::InvalidPaymentData)?;
+            events::emit_payment_events(env, &due.investment, &due.recipient, due.amount, due.periods, PaymentTrigger::Admin);
+            events::emit_penalty_event(env, token_id, due.penalty);
+        }
+
+        Storage::update_investment_with_claim(env, token_id, &due.investment);
+        if due.investment.arrears > 0 {
+            PaymentShortfall {
+                token_id,
+                amount_paid: due.amount,
+                arrears: due.investment.arrears,
+            }
+            .publish(env);
+        }
+        paid.push_back(token_id);
+    }
+
+    if !paid.is_empty() {
+        contract_balances.recalculate_from_payment_to_investor(&total_paid);
+        contract_balances.recalculate_from_penalty_payment(&payment::total_penalties(&due_payments));
+        Storage::update_contract_balances(env, &contract_balances);
+        contract_balances.emit_event(env);
+    }
+
+    Ok(BatchPaymentSummary {
+        paid,
+        skipped,
+        total_paid,
+        next_cursor,
+    })
+}
+
+fn claim_investment(
+    env: &Env,
+    token_id: u32,
+    owner: &Address,
+    trigger: PaymentTrigger,
+) -> Result<(Investment, i128), Error> {
+    validation::validate_operation_not_paused(env, Operation::Payouts)?;
+    let contract_data = Storage::get_contract_data(env);
+    validation::validate_not_defaulted(&contract_data)?;
+    validation::validate_single_payout(&contract_data)?;
+    let mut investment =Storage::get_investment(env, token_id).ok_or(Error::AddressHasNotInvested)?;
+
+    validation::validate_claim(env, &investment)?;
+    validation::validate_senior_arrears_settled(env, &contract_data, investment.tranche)?;
+
+    let num_payments =calculate_claimable_payments(env, &investment);
+    require!(
+        num_payments > 0 || investment.has_arrears(),
+        Error::AddressInvestmentNextTransferNotClaimableYet
+    );
+
+    let mut contract_balances = Storage::get_balances_or_new(env);
+    investment.accrue_late_penalty(env, &contract_data);
+    let penalty = investment.settle_penalty();
+    let amount_to_transfer = penalty + investment.process_claim(env, num_payments);
+
+    auto_top_up_reserve(env, &contract_data, &mut contract_balances, amount_to_transfer);
+    validation::validate_reserve_balance(amount_to_transfer, &contract_balances)?;
+
+    let recipient = payment::payout_recipient(env, token_id, owner);
+    let tk = get_token(env, &contract_data);
+    tk.try_transfer(&env.current_contract_address(), &recipient, &amount_to_transfer)
+        .map_err(|_| Error::RecipientCannotReceivePayment)?
+        .map_err(|_| Error::InvalidPaymentData)?;
+
+    Storage::update_investment_with_claim(env, token_id, &investment);
+    contract_balances.recalculate_from_payment_to_investor(&amount_to_transfer);
+    contract_balances.recalculate_from_penalty_payment(&penalty);
+    Storage::update_contract_balances(env, &contract_balances);
+
+    events::emit_payment_events(env, &investment, &recipient, amount_to_transfer, num_payments, trigger);
+    events::emit_penalty_event(env, token_id, penalty);
+    contract_balances.emit_event(env);
+    Ok((investment, amount_to_transfer))
+}
+
+fn claim_investments(env: &Env, owner: &Address, token_ids: &Vec<u32>) -> Result<Vec<Investment>, Error> {
+    validation::validate_operation_not_paused(env, Operation::Payouts)?;
+    let contract_data = Storage::get_contract_data(env);
+    validation::validate_not_defaulted(&contract_data)?;
+    validation::validate_single_payout(&contract_data)?;
+    let (claimable, _) = payment::collect_claimable_payments(env, &contract_data, owner, token_ids);
+    require!(
+        !claimable.is_empty(),
+        Error::AddressInvestmentNextTransferNotClaimableYet
+    );
+
+    let amount_to_transfer = payment::total_due(&claimable);
+    let mut contract_balances = Storage::get_balances_or_new(env);
+    auto_top_up_reserve(env, &contract_data, &mut contract_balances, amount_to_transfer);
+    validation::validate_reserve_balance(amount_to_transfer, &contract_balances)?;
+
+    let tk = get_token(env, &contract_data);
+    for (recipient, amount) in payment::totals_by_recipient(env, &claimable).iter() {
+        tk.try_transfer(&env.current_contract_address(), &recipient, &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+    }
+
+    let mut investments: Vec<Investment> = Vec::new(env);
+    for (token_id, due) in claimable.iter() {
+        Storage::update_investment_with_claim(env, token_id, &due.investment);
+        events::emit_payment_events(env, &due.investment, &due.recipient, due.amount, due.periods, PaymentTrigger::SelfClaim);
+        events::emit_penalty_event(env, token_id, due.penalty);
+        investments.push_back(due.investment);
+    }
+
+    contract_balances.recalculate_from_payment_to_investor(&amount_to_transfer);
+    contract_balances.recalculate_from_penalty_payment(&payment::total_penalties(&claimable));
+    Storage::update_contract_balances(env, &contract_balances);
+    contract_balances.emit_event(env);
+
+    Ok(investments)
+}
+
 #[contract]
 pub struct InvestmentContract;
 
@@ -36,9 +259,16 @@ impl InvestmentContract {
     /// * `i_rate` - The interest rate percentage (must be > 0).
     /// * `claim_block_days` - Days investors must wait before claiming returns.
     /// * `goal` - The total funding goal (must be > 0).
-    /// * `return_type` - The return model: 1=ReverseLoan, 2=Coupon.
+    /// * `return_type` - The return model: 1=ReverseLoan, 2=Coupon, 3=RevenueShare.
     /// * `return_months` - Number of months for return payments (must be > 0).
     /// * `min_per_investment` - Minimum investment amount (must be > 0).
+    /// * `max_per_investment` - Maximum investment amount (0 for no limit).
+    /// * `max_per_investor` - Maximum amount an address can invest across all its positions (0 for no limit).
+    /// * `max_investors` - Maximum number of different investors (0 for no limit).
+    /// * `funding_start_ts` - When investments open (0 to open on deployment); the contract stays `Pending` until then.
+    /// * `early_access_secs` - How long before `funding_start_ts` allowlisted or verified addresses can already invest.
+    /// * `tranches` - The tranches investors choose from, with their rate, cap and seniority (empty for a single class).
+    /// * `revenue_cap_bps` - For revenue-share campaigns, the most a position is paid, in basis points of its principal.
     ///
     /// # Errors
     ///
@@ -46,7 +276,11 @@ impl InvestmentContract {
     /// * `GoalMustBeGreaterThanZero` if goal is 0.
     /// * `ReturnMonthsMustBeGreaterThanZero` if return_months is 0.
     /// * `MinPerInvestmentMustBeGreaterThanZero` if min_per_investment is 0.
-    /// * `UnsupportedReturnType` if return_type is not 1 or 2.
+    /// * `InvalidInvestmentLimits` if a cap is negative or below min_per_investment.
+    /// * `UnsupportedReturnType` if return_type is not 1, 2 or 3.
+    /// * `InvalidRevenueCap` if a revenue-share campaign caps payouts below the principal.
+    /// * `InvalidTranche` if a tranche has a zero interest rate or a negative cap, or the caps add up to more
+    ///   than the goal.
     pub fn __constructor(
         env: Env,
         owner_addr: Address,
@@ -64,7 +298,15 @@ impl InvestmentContract {
             investment_params.return_months,
             investment_params.min_per_investment,
         )?;
-        InvestmentReturnType::from_number(investment_params.return_type).ok_or(Error::UnsupportedReturnType)?;
+        validation::validate_investment_limits(
+            investment_params.min_per_investment,
+            investment_params.max_per_investment,
+            investment_params.max_per_investor,
+        )?;
+        let return_type =
+            InvestmentReturnType::from_number(investment_params.return_type).ok_or(Error::UnsupportedReturnType)?;
+        validation::validate_revenue_cap(return_type, investment_params.revenue_cap_bps)?;
+        validation::validate_tranches(&investment_params.tranches, investment_params.goal)?;
 
         // Set the owner using OpenZeppelin Ownable
         ownable::set_owner(&env, &owner_addr);
@@ -83,7 +325,8 @@ impl InvestmentContract {
     ///
     /// Transfers the regular payment amount from the contract's reserve balance to the investor.
     /// Updates investment status, payment tracking, and claim schedules. Validates timing constraints
-    /// to ensure payments are made according to the investment schedule.
+    /// to ensure payments are made according to the investment schedule. Any arrears left by a
+    /// previous shortfall and any late-payment penalty are paid together with the installment.
     ///
     /// # Parameters
     ///
@@ -97,38 +340,120 @@ impl InvestmentContract {
     ///
     /// # Errors
     ///
+    /// * `ContractDefaulted` if the project has been declared in default.
     /// * `AddressHasNotInvested` if no investment exists for this address and timestamp.
+    /// * `PositionFrozen` if the position is frozen.
     /// * `AddressInvestmentIsNotClaimableYet` if the claimable date hasn't been reached.
     /// * `AddressInvestmentIsFinished` if all payments have been completed.
     /// * `AddressInvestmentNextTransferNotClaimableYet` if less than a month has passed since last payment.
+    /// * `ShortfallModeBatchOnly` if the shortfall mode is enabled.
+    /// * `SeniorArrearsOutstanding` if a more senior tranche is still owed arrears.
     /// * `ContractInsufficientBalance` if reserve balance is insufficient.
     /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if token transfer fails.
     #[only_owner]
     #[when_not_paused]
     pub fn process_investor_payment(env: Env, token_id: u32) -> Result<Investment, Error> {
+        validation::validate_operation_not_paused(&env, Operation::Payouts)?;
         let contract_data = Storage::get_contract_data(&env);
+        validation::validate_not_defaulted(&contract_data)?;
+        validation::validate_single_payout(&contract_data)?;
         let addr = Self::owner_of(&env, token_id);
+        let recipient = payment::payout_recipient(&env, token_id, &addr);
         let mut investment =Storage::get_investment(&env, token_id).ok_or(Error::AddressHasNotInvested)?;
 
         validation::validate_investment_payment(&env, &investment)?;
+        validation::validate_senior_arrears_settled(&env, &contract_data, investment.tranche)?;
 
         let mut contract_balances: ContractBalance = Storage::get_balances_or_new(&env);
         let tk = get_token(&env, &contract_data);
-        let amount_to_transfer: i128 = investment.process_investment_payment(&env, &contract_data);
+        investment.accrue_late_penalty(&env, &contract_data);
+        let penalty = investment.settle_penalty();
+        let amount_to_transfer: i128 = penalty + investment.settle_arrears() + investment.process_investment_payment(&env);
 
+        auto_top_up_reserve(&env, &contract_data, &mut contract_balances, amount_to_transfer);
         validation::validate_reserve_balance(amount_to_transfer, &contract_balances)?;
-        tk.try_transfer(&env.current_contract_address(), &addr, &amount_to_transfer)
+        tk.try_transfer(&env.current_contract_address(), &recipient, &amount_to_transfer)
             .map_err(|_| Error::RecipientCannotReceivePayment)?
             .map_err(|_| Error::InvalidPaymentData)?;
 
         Storage::update_investment_with_claim(&env, token_id, &investment);
         contract_balances.recalculate_from_payment_to_investor(&amount_to_transfer);
+        contract_balances.recalculate_from_penalty_payment(&penalty);
         Storage::update_contract_balances(&env, &contract_balances);
 
+        events::emit_payment_events(&env, &investment, &recipient, amount_to_transfer, 1, PaymentTrigger::Admin);
+        events::emit_penalty_event(&env, token_id, penalty);
         contract_balances.emit_event(&env);
         Ok(investment)
     }
 
+    /// Processes the scheduled payment of several investments in a single call (admin only).
+    ///
+    /// Every investment in `token_ids` which is due receives one regular payment, exactly as
+    /// `process_investor_payment` would do. Investments that are not due (not claimable yet,
+    /// finished, paid less than a month ago, nonexistent or repeated) are skipped and reported, as are
+    /// junior positions while a more senior tranche is owed arrears that the batch itself doesn't settle.
+    /// The reserve balance is checked against the whole batch before any transfer is made.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token_ids` - The token ids of the investments to pay.
+    ///
+    /// # Returns
+    ///
+    /// * A `BatchPaymentSummary` with the paid and skipped token ids and the total amount paid.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractInsufficientBalance` if reserve balance cannot cover the whole batch.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if a token transfer fails.
+    #[only_owner]
+    #[when_not_paused]
+    pub fn process_due_payments(env: Env, token_ids: Vec<u32>) -> Result<BatchPaymentSummary, Error> {
+        pay_due_investments(&env, &token_ids, None)
+    }
+
+    /// Processes the scheduled payment of all due investments, page by page (admin only).
+    ///
+    /// Walks the investments ordered by token id, starting at `start` and taking at most `limit`
+    /// of them, and pays the due ones as `process_due_payments` does. When more investments remain,
+    /// the summary's `next_cursor` holds the token id to pass as `start` in the next call.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `start` - The first token id to consider.
+    /// * `limit` - The maximum number of investments to consider.
+    ///
+    /// # Returns
+    ///
+    /// * A `BatchPaymentSummary` with the paid and skipped token ids, the total amount paid and the next cursor.
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidPageLimit` if `limit` is 0.
+    /// * `ContractInsufficientBalance` if reserve balance cannot cover the whole page.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if a token transfer fails.
+    #[only_owner]
+    #[when_not_paused]
+    pub fn process_all_due(env: Env, start: u32, limit: u32) -> Result<BatchPaymentSummary, Error> {
+        require!(limit > 0, Error::InvalidPageLimit);
+        let claims_map: Map<u32, Claim> = Storage::get_claims_map_or_new(&env);
+        let mut token_ids: Vec<u32> = Vec::new(&env);
+        let mut next_cursor: Option<u32> = None;
+
+        for token_id in claims_map.keys().iter().filter(|token_id| *token_id >= start) {
+            if token_ids.len() >= limit {
+                next_cursor = Some(token_id);
+                break;
+            }
+            token_ids.push_back(token_id);
+        }
+
+        pay_due_investments(&env, &token_ids, next_cursor)
+    }
+
     //pub fn claim(end: Env, addr: Address)
 
     /// Allows an investor to make a new investment.
@@ -136,13 +461,19 @@ impl InvestmentContract {
     /// Validates the investment amount, contract state, and funding goal constraints.
     /// Transfers tokens from the investor to the contract, splits them into project and reserve balances,
     /// creates the investment record with calculated returns, and updates the contract state.
-    /// If the funding goal is reached, changes contract state to 'FundsReached'.
+    /// The investment earns the interest rate plus any bonus of the bonus schedule that applies to it.
+    /// With a referrer, the referral share of the commission is credited to the referrer's rewards.
+    /// In campaigns with tranches the investment earns the rate of the chosen tranche and ranks by its seniority.
+    /// If the funding goal is reached, changes contract state to 'FundsReached'. The first investment
+    /// once the funding start time has passed moves the contract from 'Pending' to 'Active'.
     ///
     /// # Parameters
     ///
     /// * `env` - The execution environment.
     /// * `addr` - The investor's address (requires authentication).
     /// * `amount` - The investment amount in tokens.
+    /// * `referrer` - The address that referred the investor, if any.
+    /// * `tranche` - The index of the chosen tranche (0 in campaigns without tranches).
     ///
     /// # Returns
     ///
@@ -150,47 +481,145 @@ impl InvestmentContract {
     ///
     /// # Errors
     ///
+    /// * `FundingNotStarted` if funding hasn't started and the early-access window isn't open either.
+    /// * `NotEligibleForEarlyAccess` if within the early-access window the investor is not allowlisted or verified.
+    /// * `AddressNotAllowlisted` if the allowlist is enabled and the investor is not allowed.
+    /// * `SelfReferral` if the investor is its own referrer.
+    /// * `ContractDefaulted` if the project has been declared in default.
     /// * `AmountLessThanMinimum` if amount is below the minimum per investment.
     /// * `GoalAlreadyReached` if the funding goal has already been reached.
     /// * `AddressInsufficientBalance` if investor doesn't have enough tokens.
+    /// * `AmountExceedsMaxPerInvestment` if amount is above the maximum per investment.
+    /// * `InvestorCapExceeded` if the investor's total invested amount would exceed the maximum per investor.
+    /// * `MaxInvestorsReached` if this is a new investor and the maximum number of investors was reached.
     /// * `WouldExceedGoal` if this investment would exceed the funding goal.
+    /// * `InvalidTranche` if the tranche doesn't exist.
+    /// * `TrancheCapExceeded` if this investment would exceed the cap of the tranche.
     ///
     /// # Note
     ///
     /// * The `#[when_not_paused]` macro automatically rejects calls if the contract is paused.
     #[when_not_paused]
-    pub fn invest(env: Env, addr: Address, amount: i128) -> Result<Investment, Error> {
+    pub fn invest(
+        env: Env,
+        addr: Address,
+        amount: i128,
+        referrer: Option<Address>,
+        tranche: u32,
+    ) -> Result<Investment, Error> {
         addr.require_auth();
+        validation::validate_operation_not_paused(&env, Operation::Investing)?;
         let mut contract_data: ContractData = Storage::get_contract_data(&env);
         let tk = get_token(&env, &contract_data);
 
+        if contract_data.state == State::Pending {
+            validation::validate_funding_started(&env, &contract_data, &addr)?;
+            if env.ledger().timestamp() >= contract_data.funding_start_ts {
+                contract_data.state = State::Active;
+                Storage::update_contract_data(&env, &contract_data);
+                contract_data.state.emit_event(&env);
+            }
+        }
+
+        validation::validate_allowlisted(&env, &addr)?;
+        validation::validate_referrer(&addr, &referrer)?;
         validation::validate_investment(amount, &contract_data, tk.balance(&addr))?;
 
+        let investor_total = Storage::get_investor_total(&env, &addr);
+        let investors_count = Storage::get_investors_count(&env);
+        validation::validate_investment_caps(amount, &contract_data, investor_total, investors_count)?;
+
         let token_decimals: u8 = tk
             .decimals()
             .try_into()
             .expect("Token decimals must fit in u8")
         ;
-        let amounts: Amount = Amount::from_investment(&env, &amount, &contract_data.interest_rate, token_decimals);
+        let amounts: Amount = Amount::from_investment(&env, &amount, &contract_data.tranche_rate(tranche), token_decimals);
 
         // Validate goal before transfer
         let mut contract_balance = Storage::get_balances_or_new(&env);
+        let raised_so_far = contract_balance.received_so_far;
         validation::validate_investment_goal(
             contract_balance.received_so_far,
             amounts.get_invested_amount(),
             contract_data.goal,
         )?;
+        let tranche_raised = Storage::get_tranche_raised(&env, contract_data.round_id, tranche);
+        validation::validate_tranche_investment(
+            &contract_data,
+            tranche,
+            tranche_raised,
+            amounts.get_invested_amount(),
+        )?;
 
         tk.try_transfer(&addr, env.current_contract_address(), &amount)
             .map_err(|_| Error::RecipientCannotReceivePayment)?
             .map_err(|_| Error::InvalidPaymentData)?;
 
         contract_balance.recalculate_from_investment(&amounts);
+        let referral_reward = match referrer {
+            Some(_) => calculate_referral_reward(amounts.amount_to_commission, contract_data.referral_share_bps),
+            None => 0,
+        };
+        contract_balance.recalculate_from_referral_credit(&referral_reward);
         Storage::update_contract_balances(&env, &contract_balance);
+        Storage::update_tranche_raised(
+            &env,
+            contract_data.round_id,
+            tranche,
+            tranche_raised + amounts.get_invested_amount(),
+        );
+
+        if investor_total == 0 {
+            Storage::update_investors_count(&env, investors_count + 1);
+        }
+        Storage::update_investor_total(&env, &addr, investor_total + amount);
 
         let token_id = Base::sequential_mint(&env, &addr);
-        let addr_investment =Investment::new(&env, &contract_data, &amount, token_decimals, token_id);
+        let bonus_bps = Storage::get_bonus_schedule(&env).bonus_bps(&env, raised_so_far, amount);
+        let addr_investment = Investment::new(
+            &env,
+            &contract_data,
+            tranche,
+            bonus_bps,
+            &amount,
+            token_decimals,
+            token_id,
+        );
         Storage::update_investment_with_claim(&env, token_id, &addr_investment);
+        let mut distribution = Storage::get_distribution(&env);
+        distribution.total_weight += addr_investment.deposited;
+        Storage::update_distribution(&env, &distribution);
+        Storage::update_distribution_checkpoint(&env, token_id, distribution.reward_per_share);
+        if addr_investment.return_type == InvestmentReturnType::RevenueShare {
+            let mut revenue_pool = Storage::get_revenue_pool(&env);
+            let exit = revenue_pool.join(addr_investment.deposited, addr_investment.total);
+            Storage::update_revenue_pool(&env, &revenue_pool);
+            Storage::update_revenue_checkpoint(&env, token_id, revenue_pool.reward_per_share);
+            Storage::update_revenue_exit(&env, token_id, exit);
+        }
+
+        InvestmentCreated {
+            investor: addr.clone(),
+            token_id,
+            amount,
+            amount_to_invest: amounts.amount_to_invest,
+            amount_to_reserve_fund: amounts.amount_to_reserve_fund,
+            amount_to_commission: amounts.amount_to_commission,
+            interest_rate: addr_investment.interest_rate,
+        }
+        .publish(&env);
+
+        if let Some(referrer) = referrer.filter(|_| referral_reward > 0) {
+            let rewards = Storage::get_referral_rewards(&env, &referrer);
+            Storage::update_referral_rewards(&env, &referrer, rewards + referral_reward);
+            ReferralCredited {
+                referrer,
+                token_id,
+                amount: referral_reward,
+            }
+            .publish(&env);
+        }
 
         if contract_balance.received_so_far >= contract_data.goal {
             contract_data.state = State::FundsReached;
@@ -238,12 +667,15 @@ impl InvestmentContract {
     ///
     /// # Errors
     ///
+    /// * `ContractDefaulted` if the project has been declared in default.
     /// * `ContractInsufficientBalance` if project balance is less than the requested amount.
     /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
     #[only_owner]
     #[when_not_paused]
     pub fn single_withdrawn(env: Env, amount: i128) -> Result<bool, Error> {
+        validation::validate_operation_not_paused(&env, Operation::Withdrawals)?;
         let contract_data = Storage::get_contract_data(&env);
+        validation::validate_not_defaulted(&contract_data)?;
 
         let mut contract_balances: ContractBalance = Storage::get_balances_or_new(&env);
         validation::validate_withdrawal(amount, contract_balances.project)?;
@@ -262,11 +694,135 @@ impl InvestmentContract {
         //decrement_project_balance_from_company_withdrawal(&mut contract_balances, &amount);
         contract_balances.recalculate_from_company_withdrawal(&amount);
         Storage::update_contract_balances(&env, &contract_balances);
+
+        ProjectWithdrawal {
+            recipient: contract_data.project_address,
+            amount,
+        }
+        .publish(&env);
         contract_balances.emit_event(&env);
 
         Ok(true)
     }
 
+    /// Compares the campaign tokens held by the contract with its internal balances.
+    ///
+    /// The booked amount is the sum of the reserve, project, commission and recovery balances.
+    /// Tokens sent straight to the contract address show up as surplus; a deficit means the
+    /// bookkeeping promises more than the contract holds.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    ///
+    /// # Returns
+    ///
+    /// * `Reconciliation` with the holdings, the booked amount and the surplus or deficit.
+    pub fn reconcile(env: Env) -> Reconciliation {
+        let contract_data = Storage::get_contract_data(&env);
+        let holdings = get_token(&env, &contract_data).balance(&env.current_contract_address());
+        Storage::get_balances_or_new(&env).reconcile(holdings)
+    }
+
+    /// Sweeps part of the verified token surplus into a balance or out to the owner (admin only).
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `amount` - The amount to sweep.
+    /// * `target` - The balance receiving the surplus, or `Owner` to transfer it out to the owner.
+    ///
+    /// # Errors
+    ///
+    /// * `AmountMustBeGreaterThanZero` if amount is not positive.
+    /// * `SurplusInsufficient` if amount is above the current surplus reported by `reconcile`.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer to the owner fails.
+    #[only_owner]
+    pub fn sweep_surplus(env: Env, amount: i128, target: SweepTarget) -> Result<(), Error> {
+        require!(amount > 0, Error::AmountMustBeGreaterThanZero);
+
+        let contract_data = Storage::get_contract_data(&env);
+        let tk = get_token(&env, &contract_data);
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        let reconciliation = contract_balances.reconcile(tk.balance(&env.current_contract_address()));
+        require!(amount <= reconciliation.surplus, Error::SurplusInsufficient);
+
+        if target == SweepTarget::Owner {
+            let owner = ownable::get_owner(&env).unwrap();
+            tk.try_transfer(&env.current_contract_address(), &owner, &amount)
+                .map_err(|_| Error::RecipientCannotReceivePayment)?
+                .map_err(|_| Error::InvalidPaymentData)?;
+        }
+
+        contract_balances.recalculate_from_surplus_sweep(&target, &amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        SurplusSwept { target, amount }.publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(())
+    }
+
+    /// Moves tokens other than the campaign token out of the contract (admin only).
+    ///
+    /// Rescues SAC or SEP-41 tokens sent to the contract by mistake. The campaign token is never
+    /// touched; a surplus of it is handled by `sweep_surplus`.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token` - The address of the token to rescue.
+    /// * `to` - The address receiving the tokens.
+    /// * `amount` - The amount to rescue.
+    ///
+    /// # Errors
+    ///
+    /// * `CannotRescueCampaignToken` if `token` is the campaign token.
+    /// * `AmountMustBeGreaterThanZero` if amount is not positive.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    #[only_owner]
+    pub fn rescue_token(env: Env, token: Address, to: Address, amount: i128) -> Result<(), Error> {
+        let contract_data = Storage::get_contract_data(&env);
+        require!(token != contract_data.token, Error::CannotRescueCampaignToken);
+        require!(amount > 0, Error::AmountMustBeGreaterThanZero);
+
+        TokenClient::new(&env, &token)
+            .try_transfer(&env.current_contract_address(), &to, &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        TokenRescued { token, to, amount }.publish(&env);
+        Ok(())
+    }
+
+    /// Forecasts the payments due in a time range and their coverage by the reserve (admin only).
+    ///
+    /// The range is split into one-month buckets starting at `from_ts`. Every remaining installment of
+    /// the active investments is counted in the bucket it falls due in, including the Coupon principal
+    /// at maturity. Arrears, accrued penalties and installments already due at `from_ts` fall in the
+    /// first bucket.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `from_ts` - The start of the range (inclusive).
+    /// * `to_ts` - The end of the range (exclusive).
+    ///
+    /// # Returns
+    ///
+    /// * `ObligationsForecast` with the total due per bucket, the overall total, the current reserve and
+    ///   the coverage ratio in basis points.
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidForecastRange` if the range is empty or spans more than 120 months.
+    #[only_owner]
+    pub fn forecast_obligations(env: Env, from_ts: u64, to_ts: u64) -> Result<ObligationsForecast, Error> {
+        validation::validate_forecast_range(from_ts, to_ts)?;
+
+        let contract_balances = Storage::get_balances_or_new(&env);
+        Ok(forecast::forecast_obligations(&env, from_ts, to_ts, contract_balances.reserve))
+    }
+
     /// Calculates additional funds needed in reserve balance (admin only).
     ///
     /// Analyzes upcoming payment claims (within the next week) and compares them against
@@ -301,8 +857,9 @@ impl InvestmentContract {
 
     /// Adds funds from admin to the contract's reserve balance (admin only).
     ///
-    /// Transfers tokens from the admin address to the contract and adds them to the reserve balance.
-    /// This is used to replenish the reserve fund for upcoming investor payments.
+    /// Transfers tokens from the current owner address to the contract and adds them to the reserve balance.
+    /// This is used to replenish the reserve fund for upcoming investor payments. After an ownership
+    /// transfer has been accepted, funds are pulled from the new owner.
     ///
     /// # Parameters
     ///
@@ -315,10 +872,13 @@ impl InvestmentContract {
     ///
     /// # Errors
     ///
+    /// * `ContractDefaulted` if the project has been declared in default; recovered funds go through
+    ///   `deposit_recovery` instead.
     /// * `AddressInsufficientBalance` if admin doesn't have enough tokens.
     #[only_owner]
     pub fn add_company_transfer(env: Env, amount: i128) -> Result<bool, Error> {
         let contract_data = Storage::get_contract_data(&env);
+        validation::validate_not_defaulted(&contract_data)?;
         let owner = ownable::get_owner(&env).unwrap();
 
         let tk = get_token(&env, &contract_data);
@@ -330,6 +890,12 @@ impl InvestmentContract {
         let mut contract_balances = Storage::get_balances_or_new(&env);
         contract_balances.recalculate_from_company_contribution(&amount);
         Storage::update_contract_balances(&env, &contract_balances);
+
+        ReserveContribution {
+            from: owner,
+            amount,
+        }
+        .publish(&env);
         contract_balances.emit_event(&env);
 
         Ok(true)
@@ -351,14 +917,20 @@ impl InvestmentContract {
     ///
     /// # Errors
     ///
+    /// * `OperationPaused` if withdrawals are paused.
+    /// * `ContractDefaulted` if the project has been declared in default.
     /// * `ProjectBalanceInsufficientAmount` if project balance is less than the requested amount.
     #[only_owner]
     pub fn move_funds_to_the_reserve(env: Env, amount: i128) -> Result<bool, Error> {
+        validation::validate_operation_not_paused(&env, Operation::Withdrawals)?;
+        validation::validate_not_defaulted(&Storage::get_contract_data(&env))?;
         let mut contract_balances = Storage::get_balances_or_new(&env);
         validation::validate_move_to_reserve(amount, contract_balances.project)?;
 
         contract_balances.recalculate_from_project_to_reserver_movement(&amount);
         Storage::update_contract_balances(&env, &contract_balances);
+
+        ReserveMove { amount }.publish(&env);
         contract_balances.emit_event(&env);
 
         Ok(true)
@@ -385,10 +957,14 @@ impl InvestmentContract {
     ///
     /// # Errors
     ///
+    /// * `ContractDefaulted` if the project has been declared in default.
     /// * `AddressHasNotInvested` if no investment exists for this address and timestamp.
+    /// * `PositionFrozen` if the position is frozen.
     /// * `AddressInvestmentIsNotClaimableYet` if the claimable date hasn't been reached.
     /// * `AddressInvestmentIsFinished` if all payments have been completed.
     /// * `AddressInvestmentNextTransferNotClaimableYet` if no full payment periods have elapsed.
+    /// * `ShortfallModeBatchOnly` if the shortfall mode is enabled.
+    /// * `SeniorArrearsOutstanding` if a more senior tranche is still owed arrears.
     /// * `ContractInsufficientBalance` if reserve balance is insufficient.
     /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if token transfer fails.
     #[when_not_paused]
@@ -396,39 +972,1298 @@ impl InvestmentContract {
         let addr: Address = Self::owner_of(&env, token_id);
         addr.require_auth();
 
-        let contract_data = Storage::get_contract_data(&env);
-        let mut investment =Storage::get_investment(&env, token_id).ok_or(Error::AddressHasNotInvested)?;
-
-        validation::validate_claim(&env, &investment)?;
+        claim_investment(&env, token_id, &addr, PaymentTrigger::SelfClaim).map(|(investment, _)| investment)
+    }
 
-        let num_payments =calculate_claimable_payments(&env, &investment, contract_data.return_months);
+    /// Allows an approved delegate to claim an investor's pending payments on their behalf.
+    ///
+    /// Works exactly like `claim`, but it is authorized by a delegate the investor approved with
+    /// `set_claim_delegate`. The payment still goes to the position's payout address.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `delegate` - The delegate's address (requires authentication).
+    /// * `token_id` - The token id of the position to claim.
+    ///
+    /// # Returns
+    ///
+    /// * The updated `Investment` object with incremented payment counters.
+    ///
+    /// # Errors
+    ///
+    /// * `ClaimDelegateNotApproved` if `delegate` is not approved by the position owner.
+    /// * Any error returned by `claim`.
+    #[when_not_paused]
+    pub fn claim_as_delegate(env: Env, delegate: Address, token_id: u32) -> Result<Investment, Error> {
+        delegate.require_auth();
+        let addr: Address = Self::owner_of(&env, token_id);
         require!(
-            num_payments > 0,
-            Error::AddressInvestmentNextTransferNotClaimableYet
+            Storage::is_claim_delegate(&env, &addr, &delegate),
+            Error::ClaimDelegateNotApproved
         );
 
-        let mut contract_balances = Storage::get_balances_or_new(&env);
-        let amount_to_transfer = investment.process_multiple_payments(&env, &contract_data, num_payments);
+        claim_investment(&env, token_id, &addr, PaymentTrigger::Delegate).map(|(investment, _)| investment)
+    }
 
-        validation::validate_reserve_balance(amount_to_transfer, &contract_balances)?;
+    /// Pays the pending payments of a position on behalf of its owner, rewarding the caller.
+    ///
+    /// Anyone can call this function once a payment is due, so off-chain keepers can automate
+    /// payouts without privileged keys. Pending periods are calculated and paid to the position's
+    /// payout address exactly as `claim` does. The caller receives a bounty of `keeper_bounty_bps`
+    /// basis points of the paid amount, taken from the commission balance (and limited by it).
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `keeper` - The caller's address receiving the bounty (requires authentication).
+    /// * `token_id` - The token id of the position to pay.
+    ///
+    /// # Returns
+    ///
+    /// * The updated `Investment` object with incremented payment counters.
+    ///
+    /// # Errors
+    ///
+    /// * Any error returned by `claim`.
+    #[when_not_paused]
+    pub fn poke_payment(env: Env, keeper: Address, token_id: u32) -> Result<Investment, Error> {
+        keeper.require_auth();
+        let addr: Address = Self::owner_of(&env, token_id);
+        let (investment, amount_paid) = claim_investment(&env, token_id, &addr, PaymentTrigger::Keeper)?;
 
-        let tk = get_token(&env, &contract_data);
-        tk.try_transfer(&env.current_contract_address(), &addr, &amount_to_transfer)
-            .map_err(|_| Error::RecipientCannotReceivePayment)?
-            .map_err(|_| Error::InvalidPaymentData)?;
+        let contract_data = Storage::get_contract_data(&env);
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        let bounty = calculate_keeper_bounty(amount_paid, contract_data.keeper_bounty_bps, contract_balances.comission);
+        if bounty > 0 {
+            let tk = get_token(&env, &contract_data);
+            tk.try_transfer(&env.current_contract_address(), &keeper, &bounty)
+                .map_err(|_| Error::RecipientCannotReceivePayment)?
+                .map_err(|_| Error::InvalidPaymentData)?;
 
-        Storage::update_investment_with_claim(&env, token_id, &investment);
-        contract_balances.recalculate_from_payment_to_investor(&amount_to_transfer);
-        Storage::update_contract_balances(&env, &contract_balances);
+            contract_balances.recalculate_from_keeper_bounty(&bounty);
+            Storage::update_contract_balances(&env, &contract_balances);
+
+            KeeperRewarded {
+                keeper,
+                token_id,
+                bounty,
+            }
+            .publish(&env);
+            contract_balances.emit_event(&env);
+        }
 
-        contract_balances.emit_event(&env);
         Ok(investment)
     }
-}
 
-#[contractimpl(contracttrait)]
-impl NonFungibleToken for InvestmentContract {
-    type ContractType = Base;
+    /// Sets the bounty paid to callers of `poke_payment`, in basis points of the paid amount (admin only).
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `bounty_bps` - The bounty in basis points. Zero disables it.
+    ///
+    /// # Errors
+    ///
+    /// * `KeeperBountyTooHigh` if `bounty_bps` exceeds `MAX_KEEPER_BOUNTY_BPS`.
+    #[only_owner]
+    pub fn set_keeper_bounty(env: Env, bounty_bps: u32) -> Result<(), Error> {
+        validation::validate_keeper_bounty(bounty_bps)?;
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        contract_data.keeper_bounty_bps = bounty_bps;
+        Storage::update_contract_data(&env, &contract_data);
+        Ok(())
+    }
+
+    /// Sets the share of each investment's commission credited to its referrer (admin only).
+    ///
+    /// Referral rewards are moved from the commission balance to the `referrals` liability and can be
+    /// withdrawn by the referrer through `claim_referral_rewards`. Rewards already credited are kept.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `share_bps` - The share of the commission in basis points. Zero disables referral rewards.
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidReferralShare` if `share_bps` exceeds the whole commission.
+    #[only_owner]
+    pub fn set_referral_share(env: Env, share_bps: u32) -> Result<(), Error> {
+        validation::validate_referral_share(share_bps)?;
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        contract_data.referral_share_bps = share_bps;
+        Storage::update_contract_data(&env, &contract_data);
+        Ok(())
+    }
+
+    /// Pays a referrer the referral rewards credited to it.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `referrer` - The referrer's address (requires authentication).
+    ///
+    /// # Returns
+    ///
+    /// * The amount paid.
+    ///
+    /// # Errors
+    ///
+    /// * `NoReferralRewards` if the referrer has no rewards to claim.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    #[when_not_paused]
+    pub fn claim_referral_rewards(env: Env, referrer: Address) -> Result<i128, Error> {
+        referrer.require_auth();
+        validation::validate_operation_not_paused(&env, Operation::Payouts)?;
+
+        let amount = Storage::get_referral_rewards(&env, &referrer);
+        require!(amount > 0, Error::NoReferralRewards);
+
+        let contract_data = Storage::get_contract_data(&env);
+        let tk = get_token(&env, &contract_data);
+        tk.try_transfer(&env.current_contract_address(), &referrer, &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        Storage::update_referral_rewards(&env, &referrer, 0);
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_referral_payment(&amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        ReferralRewardsClaimed { referrer, amount }.publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(amount)
+    }
+
+    /// Returns the referral rewards credited to a referrer and not yet claimed.
+    pub fn get_referral_rewards(env: Env, referrer: Address) -> i128 {
+        Storage::get_referral_rewards(&env, &referrer)
+    }
+
+    /// Enables or disables the shortfall mode for batch payments (admin only).
+    ///
+    /// When enabled, a batch of due payments that exceeds the reserve balance no longer fails: arrears
+    /// are settled first and the rest of the reserve is shared among the due installments so each
+    /// receives the same fraction. With tranches, each seniority level is paid in full before the next
+    /// one. The unpaid part is recorded as arrears on each investment and settled first by the next
+    /// payment once the reserve is topped up.
+    ///
+    /// While enabled, every payout goes through `process_due_payments` / `process_all_due`: claims and
+    /// single payments are rejected, so no investor is paid in full from a short reserve ahead of the
+    /// others. Disable it once the reserve covers what is due to let investors claim again.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `enabled` - Whether the shortfall mode is enabled.
+    #[only_owner]
+    pub fn set_shortfall_mode(env: Env, enabled: bool) {
+        let mut contract_data = Storage::get_contract_data(&env);
+        contract_data.shortfall_mode = enabled;
+        Storage::update_contract_data(&env, &contract_data);
+    }
+
+    /// Closes the current funding round at the amount raised so far (admin only).
+    ///
+    /// The contract moves to `FundsReached` as if the goal had been reached, so no further
+    /// investments are accepted until a new round is opened.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    ///
+    /// # Errors
+    ///
+    /// * `FundingClosed` if the current round is not open.
+    #[only_owner]
+    pub fn close_funding(env: Env) -> Result<(), Error> {
+        let mut contract_data = Storage::get_contract_data(&env);
+        require!(contract_data.state == State::Active, Error::FundingClosed);
+
+        contract_data.state = State::FundsReached;
+        Storage::update_contract_data(&env, &contract_data);
+        contract_data.state.emit_event(&env);
+        Ok(())
+    }
+
+    /// Opens a follow-on funding round with its own goal, rate and terms (admin only).
+    ///
+    /// The previous round must be closed. Investments made from now on are tagged with the new round id
+    /// and keep the round's rate, return type and return months for their whole life, so positions from
+    /// different rounds carry different economics. The round goal is added on top of the amount raised so far,
+    /// and tranche caps limit what each tranche raises within the round. With tranches, investments keep
+    /// earning their tranche's rate, so `params.i_rate` must be 0.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `params` - The goal and terms of the new round.
+    ///
+    /// # Returns
+    ///
+    /// * The id of the new round.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractDefaulted` if the project has been declared in default.
+    /// * `RoundStillOpen` if the current round has not been closed or its goal not reached.
+    /// * `InterestRateMustBeGreaterThanZero`, `GoalMustBeGreaterThanZero`, `ReturnMonthsMustBeGreaterThanZero`,
+    ///   `MinPerInvestmentMustBeGreaterThanZero` or `UnsupportedReturnType` for invalid terms.
+    /// * `InvalidInvestmentLimits` if the new minimum exceeds a per-investment or per-investor cap.
+    /// * `InvalidTranche` if the tranche caps add up to more than the goal of the round, or if the round
+    ///   sets an interest rate in a campaign with tranches.
+    #[only_owner]
+    pub fn open_round(env: Env, params: RoundParams) -> Result<u32, Error> {
+        let mut contract_data = Storage::get_contract_data(&env);
+        validation::validate_not_defaulted(&contract_data)?;
+        require!(contract_data.state == State::FundsReached, Error::RoundStillOpen);
+        let return_type = validation::validate_round_params(&params, &contract_data.tranches)?;
+        validation::validate_investment_limits(
+            params.min_per_investment,
+            contract_data.max_per_investment,
+            contract_data.max_per_investor,
+        )?;
+        validation::validate_revenue_cap(return_type, contract_data.revenue_cap_bps)?;
+        validation::validate_tranches(&contract_data.tranches, params.goal)?;
+
+        let received_so_far = Storage::get_balances_or_new(&env).received_so_far;
+        contract_data.round_id += 1;
+        contract_data.goal = received_so_far + params.goal;
+        if contract_data.tranches.is_empty() {
+            contract_data.interest_rate = params.i_rate;
+        }
+        contract_data.claim_block_days = params.claim_block_days;
+        contract_data.return_type = return_type;
+        contract_data.return_months = params.return_months;
+        contract_data.min_per_investment = params.min_per_investment;
+        contract_data.state = State::Active;
+        Storage::update_contract_data(&env, &contract_data);
+
+        RoundOpened {
+            round_id: contract_data.round_id,
+            goal: params.goal,
+            interest_rate: params.i_rate,
+            claim_block_days: params.claim_block_days,
+            return_type,
+            return_months: params.return_months,
+            min_per_investment: params.min_per_investment,
+        }
+        .publish(&env);
+        contract_data.state.emit_event(&env);
+        Ok(contract_data.round_id)
+    }
+
+    /// Updates the funding goal (admin only).
+    ///
+    /// Before the first investment the goal can take any valid value. Afterwards it can only be raised;
+    /// raising it above the amount received reopens a campaign that had reached its goal.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `goal` - The new funding goal.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractDefaulted` if the project has been declared in default.
+    /// * `GoalCanOnlyBeRaised` if investments exist and the new goal is not above the current one.
+    /// * `GoalMustBeGreaterThanZero` if the goal is not positive.
+    /// * `InvalidTranche` if the tranche caps add up to more than the goal.
+    #[only_owner]
+    pub fn set_goal(env: Env, goal: i128) -> Result<(), Error> {
+        let mut contract_data = Storage::get_contract_data(&env);
+        validation::validate_not_defaulted(&contract_data)?;
+
+        let received_so_far = Storage::get_balances_or_new(&env).received_so_far;
+        require!(received_so_far == 0 || goal > contract_data.goal, Error::GoalCanOnlyBeRaised);
+
+        let old_goal = contract_data.goal;
+        contract_data.goal = goal;
+        let reopened = contract_data.state == State::FundsReached && received_so_far < goal;
+        if reopened {
+            contract_data.state = State::Active;
+        }
+
+        update_parameter(&env, &contract_data, ContractParameter::Goal, old_goal, goal)?;
+        if reopened {
+            contract_data.state.emit_event(&env);
+        }
+        Ok(())
+    }
+
+    /// Updates the minimum amount per investment (admin only). Only allowed before the first investment.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `min_per_investment` - The new minimum amount per investment.
+    ///
+    /// # Errors
+    ///
+    /// * `ParamsLockedAfterInvestment` if an investment has already been received.
+    /// * `MinPerInvestmentMustBeGreaterThanZero` if the minimum is not positive.
+    /// * `InvalidInvestmentLimits` if the minimum exceeds a per-investment or per-investor cap.
+    #[only_owner]
+    pub fn set_min_per_investment(env: Env, min_per_investment: i128) -> Result<(), Error> {
+        validation::validate_params_unlocked(Storage::get_balances_or_new(&env).received_so_far)?;
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        let old_min = contract_data.min_per_investment;
+        contract_data.min_per_investment = min_per_investment;
+        update_parameter(&env, &contract_data, ContractParameter::MinPerInvestment, old_min, min_per_investment)
+    }
+
+    /// Updates the days an investment stays blocked before its first payment (admin only).
+    /// Only allowed before the first investment.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `claim_block_days` - The new number of blocked days.
+    ///
+    /// # Errors
+    ///
+    /// * `ParamsLockedAfterInvestment` if an investment has already been received.
+    #[only_owner]
+    pub fn set_claim_block_days(env: Env, claim_block_days: u64) -> Result<(), Error> {
+        validation::validate_params_unlocked(Storage::get_balances_or_new(&env).received_so_far)?;
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        let old_days = contract_data.claim_block_days;
+        contract_data.claim_block_days = claim_block_days;
+        update_parameter(
+            &env,
+            &contract_data,
+            ContractParameter::ClaimBlockDays,
+            old_days as i128,
+            claim_block_days as i128,
+        )
+    }
+
+    /// Proposes a new address receiving project withdrawals (admin only).
+    ///
+    /// The change only takes effect one week later through `apply_project_address`, giving investors
+    /// time to react. Calling it again replaces the pending change and restarts the delay.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `project_address` - The new project address.
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidProjectAddress` if the address is the current project address or the contract itself.
+    #[only_owner]
+    pub fn set_project_address(env: Env, project_address: Address) -> Result<(), Error> {
+        validation::validate_project_address(&env, &Storage::get_contract_data(&env), &project_address)?;
+
+        let pending = PendingProjectAddress {
+            address: project_address.clone(),
+            effective_ts: env.ledger().timestamp() + PROJECT_ADDRESS_CHANGE_DELAY,
+        };
+        Storage::update_pending_project_address(&env, &Some(pending.clone()));
+
+        ProjectAddressChangeProposed {
+            project_address,
+            effective_ts: pending.effective_ts,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Applies a pending project address change once its delay has elapsed (admin only).
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    ///
+    /// # Errors
+    ///
+    /// * `ProjectAddressChangeNotReady` if there is no pending change or its delay has not elapsed.
+    #[only_owner]
+    pub fn apply_project_address(env: Env) -> Result<(), Error> {
+        let pending = Storage::get_pending_project_address(&env).ok_or(Error::ProjectAddressChangeNotReady)?;
+        require!(
+            env.ledger().timestamp() >= pending.effective_ts,
+            Error::ProjectAddressChangeNotReady
+        );
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        contract_data.project_address = pending.address.clone();
+        Storage::update_contract_data(&env, &contract_data);
+        Storage::update_pending_project_address(&env, &None);
+
+        ProjectAddressUpdated {
+            project_address: pending.address,
+            effective_ts: env.ledger().timestamp(),
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns the pending project address change, if any.
+    pub fn get_pending_project_address(env: Env) -> Option<PendingProjectAddress> {
+        Storage::get_pending_project_address(&env)
+    }
+
+    /// Sets the automatic reserve top-up policy (admin only).
+    ///
+    /// When enabled, a payout that finds the reserve insufficient first moves the missing amount from
+    /// the project balance to the reserve, up to `cap` per payout, before failing. Automatic movements
+    /// are recorded in `moved_from_project_to_reserve` like manual ones and emit a `ReserveAutoTopUp` event.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `enabled` - Whether the policy is enabled.
+    /// * `cap` - The maximum amount moved per payout (0 means no limit).
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidAutoTopUpCap` if the cap is negative.
+    #[only_owner]
+    pub fn set_auto_top_up(env: Env, enabled: bool, cap: i128) -> Result<(), Error> {
+        require!(cap >= 0, Error::InvalidAutoTopUpCap);
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        contract_data.auto_top_up = enabled;
+        contract_data.auto_top_up_cap = cap;
+        Storage::update_contract_data(&env, &contract_data);
+        Ok(())
+    }
+
+    /// Sets the bonus schedule of new investments (admin only).
+    ///
+    /// Investments made while the early-bird conditions hold (less than `early_bird_raised` raised, or
+    /// before `early_bird_end_ts`) earn `early_bird_bps` on top of the interest rate, and investments of at
+    /// least `volume_threshold` earn `volume_bonus_bps`. Existing investments keep their rate.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `schedule` - The bonus schedule (a default schedule disables bonuses).
+    ///
+    /// # Errors
+    ///
+    /// * `InvalidBonusSchedule` if the bonuses add up to more than 10%, the early-bird amount is negative
+    ///   or a volume bonus has no positive threshold.
+    #[only_owner]
+    pub fn set_bonus_schedule(env: Env, schedule: BonusSchedule) -> Result<(), Error> {
+        validation::validate_bonus_schedule(&schedule)?;
+        Storage::update_bonus_schedule(&env, &schedule);
+        Ok(())
+    }
+
+    /// Returns the bonus schedule of new investments.
+    pub fn get_bonus_schedule(env: Env) -> BonusSchedule {
+        Storage::get_bonus_schedule(&env)
+    }
+
+    /// Sets the late-payment penalty (admin only).
+    ///
+    /// Arrears left by a partial payment (see `set_shortfall_mode`) accrue `penalty_bps` of their
+    /// amount as penalty interest on the investment for every full period they stay unpaid past the
+    /// grace window. Installments the reserve could cover never accrue penalty, however late they are
+    /// claimed. The penalty is paid from the reserve together with the next successful payout and tracked
+    /// in `penalties_paid`; the project stops it by processing due payments once the reserve is topped up.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `penalty_bps` - The penalty per late period, in basis points of the overdue amount (0 disables it).
+    /// * `grace_days` - The days an installment can stay unpaid before accruing penalty.
+    ///
+    /// # Errors
+    ///
+    /// * `LatePenaltyTooHigh` if the penalty exceeds 10% per period.
+    /// * `LateGraceTooLong` if the grace window exceeds a year.
+    #[only_owner]
+    pub fn set_late_penalty(env: Env, penalty_bps: u32, grace_days: u64) -> Result<(), Error> {
+        validation::validate_late_penalty(penalty_bps, grace_days)?;
+
+        let mut contract_data = Storage::get_contract_data(&env);
+        contract_data.late_penalty_bps = penalty_bps;
+        contract_data.late_grace_days = grace_days;
+        Storage::update_contract_data(&env, &contract_data);
+        Ok(())
+    }
+
+    /// Sets the address where the payments of a position are sent.
+    ///
+    /// Only the position owner can set it. Passing `None` sends payments to the owner again.
+    /// The payout address is cleared whenever the position NFT is transferred.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token_id` - The token id of the position.
+    /// * `payout_address` - The address receiving the payments, or `None` to pay the owner.
+    pub fn set_payout_address(env: Env, token_id: u32, payout_address: Option<Address>) {
+        let addr: Address = Self::owner_of(&env, token_id);
+        addr.require_auth();
+
+        Storage::update_payout_address(&env, token_id, &payout_address);
+        PayoutAddressUpdated {
+            token_id,
+            payout_address,
+        }
+        .publish(&env);
+    }
+
+    /// Returns the address where the payments of a position are sent.
+    ///
+    /// This is the payout address set by the owner or, if none is set, the owner itself.
+    pub fn get_payout_address(env: Env, token_id: u32) -> Address {
+        let addr: Address = Self::owner_of(&env, token_id);
+        payment::payout_recipient(&env, token_id, &addr)
+    }
+
+    /// Approves or revokes a delegate allowed to call `claim_as_delegate` for the owner's positions.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `owner` - The investor's address (requires authentication).
+    /// * `delegate` - The delegate's address.
+    /// * `approved` - Whether the delegate is approved.
+    pub fn set_claim_delegate(env: Env, owner: Address, delegate: Address, approved: bool) {
+        owner.require_auth();
+
+        Storage::update_claim_delegate(&env, &owner, &delegate, approved);
+        ClaimDelegateUpdated {
+            owner,
+            delegate,
+            approved,
+        }
+        .publish(&env);
+    }
+
+    /// Enables or disables investor allowlisting (admin only).
+    ///
+    /// While enabled, only allowlisted or externally verified addresses can invest or
+    /// receive position NFTs.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `enabled` - Whether the allowlist is enforced.
+    #[only_owner]
+    pub fn set_allowlist_enabled(env: Env, enabled: bool) {
+        let mut config = Storage::get_allowlist_config(&env);
+        config.enabled = enabled;
+        Storage::update_allowlist_config(&env, &config);
+
+        AllowlistConfigUpdated {
+            enabled: config.enabled,
+            verifier: config.verifier,
+        }
+        .publish(&env);
+    }
+
+    /// Sets the external verifier contract queried for addresses not in the allowlist (admin only).
+    ///
+    /// The verifier must implement the `InvestorVerifier` interface. Passing `None` removes it.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `verifier` - The verifier contract address, or `None`.
+    #[only_owner]
+    pub fn set_investor_verifier(env: Env, verifier: Option<Address>) {
+        let mut config = Storage::get_allowlist_config(&env);
+        config.verifier = verifier;
+        Storage::update_allowlist_config(&env, &config);
+
+        AllowlistConfigUpdated {
+            enabled: config.enabled,
+            verifier: config.verifier,
+        }
+        .publish(&env);
+    }
+
+    /// Adds addresses to or removes them from the allowlist (admin only).
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `investors` - The addresses to update.
+    /// * `allowed` - Whether the addresses are allowlisted.
+    #[only_owner]
+    pub fn set_allowlisted(env: Env, investors: Vec<Address>, allowed: bool) {
+        for investor in investors.iter() {
+            Storage::update_allowlisted(&env, &investor, allowed);
+            AllowlistUpdated { investor, allowed }.publish(&env);
+        }
+    }
+
+    /// Returns the allowlist configuration.
+    pub fn get_allowlist_config(env: Env) -> AllowlistConfig {
+        Storage::get_allowlist_config(&env)
+    }
+
+    /// Returns whether `investor` may currently invest and receive position NFTs.
+    pub fn is_investor_allowed(env: Env, investor: Address) -> bool {
+        allowlist::is_allowed(&env, &investor)
+    }
+
+    /// Declares the project in default (admin only).
+    ///
+    /// Freezes the normal payment schedule: investments and investor payments are rejected from now on.
+    /// The outstanding balance (`total - paid`) of every unfinished position is recorded so that
+    /// recovered funds can be distributed pro-rata, and the remaining reserve and project balances are
+    /// moved to the recovery pool since they belong to investors.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractDefaulted` if the project has already been declared in default.
+    #[only_owner]
+    pub fn declare_default(env: Env) -> Result<(), Error> {
+        let mut contract_data = Storage::get_contract_data(&env);
+        validation::validate_not_defaulted(&contract_data)?;
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        let outstanding = recovery::outstanding_by_seniority(&env, &contract_data);
+        let pool = RecoveryPool {
+            outstanding: outstanding.values().iter().sum(),
+            deposited: contract_balances.reserve + contract_balances.project,
+            distributed: 0,
+            outstanding_by_seniority: outstanding,
+        };
+        contract_balances.recalculate_from_default();
+        Storage::update_recovery_pool(&env, &pool);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        contract_data.state = State::Defaulted;
+        Storage::update_contract_data(&env, &contract_data);
+
+        contract_data.state.emit_event(&env);
+        contract_balances.emit_event(&env);
+        Ok(())
+    }
+
+    /// Deposits recovered funds to be distributed among investors after a default.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `from` - The address providing the funds (requires authentication).
+    /// * `amount` - The amount recovered.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractNotDefaulted` if the project has not been declared in default.
+    /// * `AmountMustBeGreaterThanZero` if amount is not positive.
+    /// * `AddressInsufficientBalance` if `from` doesn't have enough tokens.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    pub fn deposit_recovery(env: Env, from: Address, amount: i128) -> Result<(), Error> {
+        from.require_auth();
+        let contract_data = Storage::get_contract_data(&env);
+        validation::validate_defaulted(&contract_data)?;
+        require!(amount > 0, Error::AmountMustBeGreaterThanZero);
+
+        let tk = get_token(&env, &contract_data);
+        validation::validate_company_transfer(&tk, &from, amount)?;
+        tk.try_transfer(&from, env.current_contract_address(), &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        let mut pool = Storage::get_recovery_pool(&env);
+        pool.deposited += amount;
+        Storage::update_recovery_pool(&env, &pool);
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_recovery_deposit(&amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        RecoveryDeposited { from, amount }.publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(())
+    }
+
+    /// Claims a position's share of the recovered funds after a default.
+    ///
+    /// Each position is entitled to the recovered funds in proportion to its outstanding balance
+    /// (`total - paid`) at the time of the default. Senior tranches are covered in full before junior
+    /// tranches receive anything. It can be called again as more funds are recovered.
+    /// Payments go to the position's payout address.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token_id` - The token id of the position.
+    ///
+    /// # Returns
+    ///
+    /// * The amount paid.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractNotDefaulted` if the project has not been declared in default.
+    /// * `PositionFrozen` if the position is frozen.
+    /// * `AddressHasNotInvested` if the position does not exist.
+    /// * `NothingToRecover` if the position has already claimed its current share.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    #[when_not_paused]
+    pub fn claim_recovery(env: Env, token_id: u32) -> Result<i128, Error> {
+        let addr: Address = Self::owner_of(&env, token_id);
+        addr.require_auth();
+
+        validation::validate_operation_not_paused(&env, Operation::Payouts)?;
+        let contract_data = Storage::get_contract_data(&env);
+        validation::validate_defaulted(&contract_data)?;
+        validation::validate_position_not_frozen(&env, token_id)?;
+
+        let investment = Storage::get_investment(&env, token_id).ok_or(Error::AddressHasNotInvested)?;
+        let mut pool = Storage::get_recovery_pool(&env);
+        let claimed = Storage::get_recovery_claimed(&env, token_id);
+        let seniority = contract_data.tranche_seniority(investment.tranche);
+        let amount = pool.entitlement(recovery::outstanding_balance(&investment), seniority) - claimed;
+        require!(amount > 0, Error::NothingToRecover);
+
+        let recipient = payment::payout_recipient(&env, token_id, &addr);
+        let tk = get_token(&env, &contract_data);
+        tk.try_transfer(&env.current_contract_address(), &recipient, &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        Storage::update_recovery_claimed(&env, token_id, claimed + amount);
+        pool.distributed += amount;
+        Storage::update_recovery_pool(&env, &pool);
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_recovery_payment(&amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        RecoveryClaimed {
+            token_id,
+            recipient,
+            amount,
+        }
+        .publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(amount)
+    }
+
+    /// Reports the revenue of a period and deposits the investors' share of it (project only).
+    ///
+    /// The share is distributed among the revenue-share positions still earning, in proportion to
+    /// their `deposited` amount, and each position collects it through `claim_revenue`. A position
+    /// stops sharing as soon as it has earned its cap, claimed or not, and the rest goes to the others.
+    /// Only the part the positions can still earn is taken from the project address.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `period` - The period the revenue belongs to; periods must be reported in increasing order.
+    /// * `amount` - The investors' share of the revenue, transferred from the project address.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractDefaulted` if the project has been declared in default.
+    /// * `AmountMustBeGreaterThanZero` if amount is not positive.
+    /// * `RevenuePeriodAlreadyReported` if the period is not after the last reported one.
+    /// * `NoRevenueSharePositions` if no revenue-share position is earning.
+    /// * `AddressInsufficientBalance` if the project address doesn't have enough tokens.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    #[when_not_paused]
+    pub fn report_revenue(env: Env, period: u32, amount: i128) -> Result<(), Error> {
+        let contract_data = Storage::get_contract_data(&env);
+        contract_data.project_address.require_auth();
+        validation::validate_not_defaulted(&contract_data)?;
+        require!(amount > 0, Error::AmountMustBeGreaterThanZero);
+
+        let mut pool = Storage::get_revenue_pool(&env);
+        validation::validate_revenue_report(&pool, period)?;
+        let distributed = pool.report(period, amount);
+
+        let tk = get_token(&env, &contract_data);
+        validation::validate_company_transfer(&tk, &contract_data.project_address, distributed)?;
+        tk.try_transfer(&contract_data.project_address, env.current_contract_address(), &distributed)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        Storage::update_revenue_pool(&env, &pool);
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_revenue_report(&distributed);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        RevenueReported {
+            period,
+            amount: distributed,
+            reward_per_share: pool.reward_per_share,
+        }
+        .publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(())
+    }
+
+    /// Claims the revenue a revenue-share position has earned since its last claim.
+    ///
+    /// Payouts stop at the position's cap (`total`), at which point the position is finished. It stopped
+    /// sharing in reports as soon as it earned its cap. Payments go to the position's payout address.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token_id` - The token id of the position.
+    ///
+    /// # Returns
+    ///
+    /// * The updated `Investment`.
+    ///
+    /// # Errors
+    ///
+    /// * `AddressHasNotInvested` if the position does not exist.
+    /// * `PositionFrozen` if the position is frozen.
+    /// * `NotRevenueShare` if the position doesn't have the revenue-share return type.
+    /// * `AddressInvestmentIsNotClaimableYet` if the claim block period has not elapsed.
+    /// * `AddressInvestmentIsFinished` if the position has been paid its cap.
+    /// * `NoRevenueToClaim` if the position has earned nothing since its last claim.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    #[when_not_paused]
+    pub fn claim_revenue(env: Env, token_id: u32) -> Result<Investment, Error> {
+        let addr: Address = Self::owner_of(&env, token_id);
+        addr.require_auth();
+
+        validation::validate_operation_not_paused(&env, Operation::Payouts)?;
+        let mut investment = Storage::get_investment(&env, token_id).ok_or(Error::AddressHasNotInvested)?;
+        validation::validate_revenue_claim(&env, &investment)?;
+
+        let mut pool = Storage::get_revenue_pool(&env);
+        let amount = pool.pending(
+            investment.deposited,
+            Storage::get_revenue_checkpoint(&env, token_id),
+            Storage::get_revenue_exit(&env, token_id),
+            investment.total - investment.paid,
+        );
+        require!(amount > 0, Error::NoRevenueToClaim);
+
+        let contract_data = Storage::get_contract_data(&env);
+        let recipient = payment::payout_recipient(&env, token_id, &addr);
+        let tk = get_token(&env, &contract_data);
+        tk.try_transfer(&env.current_contract_address(), &recipient, &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        investment.process_revenue_payment(&env, amount);
+        Storage::update_investment_with_claim(&env, token_id, &investment);
+        pool.distributed += amount;
+        Storage::update_revenue_pool(&env, &pool);
+        Storage::update_revenue_checkpoint(&env, token_id, pool.reward_per_share);
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_revenue_payment(&amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        events::emit_payment_events(&env, &investment, &recipient, amount, 1, PaymentTrigger::SelfClaim);
+        contract_balances.emit_event(&env);
+        Ok(investment)
+    }
+
+    /// Returns the revenue reported and distributed so far, with the accumulated revenue per unit of weight.
+    pub fn get_revenue_pool(env: Env) -> RevenuePool {
+        Storage::get_revenue_pool(&env)
+    }
+
+    /// Distributes an ad-hoc amount (a bonus or a surplus return) among all positions (project only).
+    ///
+    /// The amount is shared in proportion to the `deposited` amount of every position, including
+    /// finished ones, and each position collects its share through `claim_distribution`.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `amount` - The amount to distribute, transferred from the project address.
+    ///
+    /// # Errors
+    ///
+    /// * `ContractDefaulted` if the project has been declared in default.
+    /// * `AmountMustBeGreaterThanZero` if amount is not positive.
+    /// * `NoPositionsToDistribute` if there are no positions yet.
+    /// * `AddressInsufficientBalance` if the project address doesn't have enough tokens.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    pub fn distribute(env: Env, amount: i128) -> Result<(), Error> {
+        let contract_data = Storage::get_contract_data(&env);
+        contract_data.project_address.require_auth();
+        validation::validate_not_defaulted(&contract_data)?;
+        require!(amount > 0, Error::AmountMustBeGreaterThanZero);
+
+        let mut distribution = Storage::get_distribution(&env);
+        require!(distribution.total_weight > 0, Error::NoPositionsToDistribute);
+
+        let tk = get_token(&env, &contract_data);
+        validation::validate_company_transfer(&tk, &contract_data.project_address, amount)?;
+        tk.try_transfer(&contract_data.project_address, env.current_contract_address(), &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        distribution.distribute(amount);
+        Storage::update_distribution(&env, &distribution);
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_distribution(&amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        Distributed {
+            amount,
+            reward_per_share: distribution.reward_per_share,
+        }
+        .publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(())
+    }
+
+    /// Claims a position's share of the distributions made since its last claim.
+    ///
+    /// Payments go to the position's payout address.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token_id` - The token id of the position.
+    ///
+    /// # Returns
+    ///
+    /// * The amount paid.
+    ///
+    /// # Errors
+    ///
+    /// * `PositionFrozen` if the position is frozen.
+    /// * `AddressHasNotInvested` if the position does not exist.
+    /// * `NoDistributionToClaim` if nothing has been distributed since the position's last claim.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if the transfer fails.
+    #[when_not_paused]
+    pub fn claim_distribution(env: Env, token_id: u32) -> Result<i128, Error> {
+        let addr: Address = Self::owner_of(&env, token_id);
+        addr.require_auth();
+
+        validation::validate_operation_not_paused(&env, Operation::Payouts)?;
+        validation::validate_position_not_frozen(&env, token_id)?;
+        let investment = Storage::get_investment(&env, token_id).ok_or(Error::AddressHasNotInvested)?;
+
+        let mut distribution = Storage::get_distribution(&env);
+        let checkpoint = Storage::get_distribution_checkpoint(&env, token_id);
+        let amount = distribution.pending(investment.deposited, checkpoint);
+        require!(amount > 0, Error::NoDistributionToClaim);
+
+        let contract_data = Storage::get_contract_data(&env);
+        let recipient = payment::payout_recipient(&env, token_id, &addr);
+        let tk = get_token(&env, &contract_data);
+        tk.try_transfer(&env.current_contract_address(), &recipient, &amount)
+            .map_err(|_| Error::RecipientCannotReceivePayment)?
+            .map_err(|_| Error::InvalidPaymentData)?;
+
+        distribution.claimed += amount;
+        Storage::update_distribution(&env, &distribution);
+        Storage::update_distribution_checkpoint(&env, token_id, distribution.reward_per_share);
+
+        let mut contract_balances = Storage::get_balances_or_new(&env);
+        contract_balances.recalculate_from_distribution_payment(&amount);
+        Storage::update_contract_balances(&env, &contract_balances);
+
+        DistributionClaimed {
+            token_id,
+            recipient,
+            amount,
+        }
+        .publish(&env);
+        contract_balances.emit_event(&env);
+        Ok(amount)
+    }
+
+    /// Returns the amounts distributed and claimed and the accumulated distribution per unit of weight.
+    pub fn get_distribution(env: Env) -> Distribution {
+        Storage::get_distribution(&env)
+    }
+
+    /// Returns the recovery pool: outstanding balance at default, funds recovered and funds distributed.
+    pub fn get_recovery_pool(env: Env) -> RecoveryPool {
+        Storage::get_recovery_pool(&env)
+    }
+
+    /// Sets the compliance officer allowed to freeze, unfreeze and seize positions (admin only).
+    ///
+    /// The owner can always perform those actions. Passing `None` removes the officer.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `officer` - The compliance officer's address, or `None`.
+    #[only_owner]
+    pub fn set_compliance_officer(env: Env, officer: Option<Address>) {
+        Storage::update_compliance_officer(&env, &officer);
+        ComplianceOfficerUpdated { officer }.publish(&env);
+    }
+
+    /// Freezes a position (owner or compliance officer).
+    ///
+    /// A frozen position cannot be claimed, paid or transferred until it is unfrozen.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `caller` - The owner or compliance officer (requires authentication).
+    /// * `token_id` - The token id of the position.
+    ///
+    /// # Errors
+    ///
+    /// * `CallerNotComplianceOfficer` if `caller` is neither the owner nor the compliance officer.
+    /// * `AddressHasNotInvested` if the position does not exist.
+    pub fn freeze_position(env: Env, caller: Address, token_id: u32) -> Result<(), Error> {
+        enforce_compliance_auth(&env, &caller)?;
+        require!(
+            Storage::get_investment(&env, token_id).is_some(),
+            Error::AddressHasNotInvested
+        );
+
+        Storage::update_position_frozen(&env, token_id, true);
+        PositionFreezeUpdated {
+            token_id,
+            caller,
+            frozen: true,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Unfreezes a position (owner or compliance officer).
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `caller` - The owner or compliance officer (requires authentication).
+    /// * `token_id` - The token id of the position.
+    ///
+    /// # Errors
+    ///
+    /// * `CallerNotComplianceOfficer` if `caller` is neither the owner nor the compliance officer.
+    /// * `PositionNotFrozen` if the position is not frozen.
+    pub fn unfreeze_position(env: Env, caller: Address, token_id: u32) -> Result<(), Error> {
+        enforce_compliance_auth(&env, &caller)?;
+        require!(
+            Storage::is_position_frozen(&env, token_id),
+            Error::PositionNotFrozen
+        );
+
+        Storage::update_position_frozen(&env, token_id, false);
+        PositionFreezeUpdated {
+            token_id,
+            caller,
+            frozen: false,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Forces the transfer of a frozen position to a recovery address (owner or compliance officer).
+    ///
+    /// The position stays frozen after the transfer and its payout address is cleared. Its principal is
+    /// moved from the investor total of the previous holder to that of `recovery`, so the seized funds
+    /// count towards the caps of the new holder and no longer towards those of the previous one.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `caller` - The owner or compliance officer (requires authentication).
+    /// * `token_id` - The token id of the position.
+    /// * `recovery` - The address receiving the position.
+    ///
+    /// # Errors
+    ///
+    /// * `CallerNotComplianceOfficer` if `caller` is neither the owner nor the compliance officer.
+    /// * `PositionNotFrozen` if the position is not frozen.
+    /// * `AddressHasNotInvested` if the position does not exist.
+    pub fn seize_position(env: Env, caller: Address, token_id: u32, recovery: Address) -> Result<(), Error> {
+        enforce_compliance_auth(&env, &caller)?;
+        require!(
+            Storage::is_position_frozen(&env, token_id),
+            Error::PositionNotFrozen
+        );
+
+        require!(
+            Storage::get_investment(&env, token_id).is_some(),
+            Error::AddressHasNotInvested
+        );
+        let from: Address = Self::owner_of(&env, token_id);
+        Base::update(&env, Some(&from), Some(&recovery), token_id);
+        Storage::update_payout_address(&env, token_id, &None);
+        move_position_total(&env, &from, &recovery, token_id);
+
+        PositionSeized {
+            token_id,
+            caller,
+            from,
+            to: recovery,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Returns whether a position is frozen.
+    pub fn is_position_frozen(env: Env, token_id: u32) -> bool {
+        Storage::is_position_frozen(&env, token_id)
+    }
+
+    /// Pauses or resumes a single group of operations (admin only).
+    ///
+    /// Unlike `pause`, which stops every guarded function, this only affects the given group:
+    /// `Investing` (invest), `Payouts` (claims and investor payments), `Withdrawals` (project
+    /// withdrawals and moves to the reserve) or `NftTransfers` (position NFT transfers). Both pauses apply independently.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `operation` - The group of operations.
+    /// * `paused` - Whether the group is paused.
+    #[only_owner]
+    pub fn set_operation_paused(env: Env, operation: Operation, paused: bool) {
+        Storage::update_operation_paused(&env, operation, paused);
+        OperationPauseUpdated { operation, paused }.publish(&env);
+    }
+
+    /// Returns whether a group of operations is paused.
+    pub fn is_operation_paused(env: Env, operation: Operation) -> bool {
+        Storage::is_operation_paused(&env, operation)
+    }
+
+    /// Returns whether `delegate` may claim the payments of `owner`'s positions.
+    pub fn is_claim_delegate(env: Env, owner: Address, delegate: Address) -> bool {
+        Storage::is_claim_delegate(&env, &owner, &delegate)
+    }
+
+    /// Allows an investor to claim the pending payments of several positions at once.
+    ///
+    /// All the positions must belong to the same investor. The claimable payments of each position
+    /// are calculated as in `claim`, added up and sent in a single token transfer. Positions with
+    /// nothing to claim yet, or ranking below a tranche still owed arrears, are left untouched. If the reserve cannot cover the total, nothing is paid.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `token_ids` - The token ids of the positions to claim.
+    ///
+    /// # Returns
+    ///
+    /// * The updated `Investment` objects of the positions that were paid.
+    ///
+    /// # Errors
+    ///
+    /// * `AddressHasNotInvested` if `token_ids` is empty.
+    /// * `TokenNotOwnedByClaimer` if the positions do not all belong to the same investor.
+    /// * `AddressInvestmentNextTransferNotClaimableYet` if no position has claimable payments.
+    /// * `ShortfallModeBatchOnly` if the shortfall mode is enabled.
+    /// * `ContractInsufficientBalance` if reserve balance cannot cover the total.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if token transfer fails.
+    #[when_not_paused]
+    pub fn claim_many(env: Env, token_ids: Vec<u32>) -> Result<Vec<Investment>, Error> {
+        let first_token_id = token_ids.first().ok_or(Error::AddressHasNotInvested)?;
+        let addr: Address = Self::owner_of(&env, first_token_id);
+        addr.require_auth();
+
+        for token_id in token_ids.iter() {
+            require!(
+                Self::owner_of(&env, token_id) == addr,
+                Error::TokenNotOwnedByClaimer
+            );
+        }
+
+        claim_investments(&env, &addr, &token_ids)
+    }
+
+    /// Allows an investor to claim the pending payments of all their positions at once.
+    ///
+    /// Works like `claim_many` over every position currently owned by `owner`. Finding those
+    /// positions requires walking all the investments of the contract.
+    ///
+    /// # Parameters
+    ///
+    /// * `env` - The execution environment.
+    /// * `owner` - The investor's address (requires authentication).
+    ///
+    /// # Returns
+    ///
+    /// * The updated `Investment` objects of the positions that were paid.
+    ///
+    /// # Errors
+    ///
+    /// * `AddressInvestmentNextTransferNotClaimableYet` if no position has claimable payments.
+    /// * `ShortfallModeBatchOnly` if the shortfall mode is enabled.
+    /// * `ContractInsufficientBalance` if reserve balance cannot cover the total.
+    /// * `RecipientCannotReceivePayment` or `InvalidPaymentData` if token transfer fails.
+    #[when_not_paused]
+    pub fn claim_all(env: Env, owner: Address) -> Result<Vec<Investment>, Error> {
+        owner.require_auth();
+
+        let claims_map: Map<u32, Claim> = Storage::get_claims_map_or_new(&env);
+        let mut token_ids: Vec<u32> = Vec::new(&env);
+        for token_id in claims_map.keys().iter() {
+            if Self::owner_of(&env, token_id) == owner {
+                token_ids.push_back(token_id);
+            }
+        }
+
+        claim_investments(&env, &owner, &token_ids)
+    }
+}
+
+#[contractimpl(contracttrait)]
+impl NonFungibleToken for InvestmentContract {
+    type ContractType = Base;
+
+    fn transfer(e: &Env, from: Address, to: Address, token_id: u32) {
+        if let Err(err) = validation::validate_operation_not_paused(e, Operation::NftTransfers)
+            .and_then(|_| validation::validate_position_not_frozen(e, token_id))
+            .and_then(|_| validation::validate_allowlisted(e, &to))
+        {
+            panic_with_error!(e, err);
+        }
+
+        Base::transfer(e, &from, &to, token_id);
+        Storage::update_payout_address(e, token_id, &None);
+        move_position_total(e, &from, &to, token_id);
+    }
+
+    fn transfer_from(e: &Env, spender: Address, from: Address, to: Address, token_id: u32) {
+        if let Err(err) = validation::validate_operation_not_paused(e, Operation::NftTransfers)
+            .and_then(|_| validation::validate_position_not_frozen(e, token_id))
+            .and_then(|_| validation::validate_allowlisted(e, &to))
+        {
+            panic_with_error!(e, err);
+        }
+
+        Base::transfer_from(e, &spender, &from, &to, token_id);
+        Storage::update_payout_address(e, token_id, &None);
+        move_position_total(e, &from, &to, token_id);
+    }
+}
+
+#[contractimpl(contracttrait)]
+impl Ownable for InvestmentContract {
+    /// Renounces ownership of the contract (owner only).
+    ///
+    /// Ownership transfers are two-step: the owner proposes a new owner with `transfer_ownership`
+    /// (which expires at the given ledger) and the proposed owner calls `accept_ownership`.
+    /// Renouncing is blocked while any investment is still pending payments, since nobody would
+    /// be left to process payments or replenish the reserve.
+    ///
+    /// # Errors
+    ///
+    /// * `OutstandingInvestmentsExist` if any investment has not been fully paid yet.
+    fn renounce_ownership(e: &Env) {
+        if Storage::has_outstanding_investments(e) {
+            panic_with_error!(e, Error::OutstandingInvestmentsExist);
+        }
+
+        ownable::renounce_ownership(e);
+    }
 }
 
 #[contractimpl]
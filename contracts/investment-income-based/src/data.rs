@@ -1,4 +1,4 @@
-use soroban_sdk::{contractevent, contracttype, Address, Env};
+use soroban_sdk::{contractevent, contracttype, Address, Env, Vec};
 
 use crate::investment::InvestmentReturnType;
 
@@ -13,8 +13,10 @@ pub trait FromNumber {
 #[repr(u32)]
 #[contracttype]
 pub enum State {
+    Pending = 1,
     Active = 2,
     FundsReached = 3,
+    Defaulted = 4,
 }
 
 impl State {
@@ -24,6 +26,69 @@ impl State {
     }
 }
 
+/// Groups of operations that can be paused independently of the global pause
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+#[contracttype]
+pub enum Operation {
+    Investing = 1,
+    Payouts = 2,
+    Withdrawals = 3,
+    NftTransfers = 4,
+}
+
+/// Goal and terms of a follow-on funding round. `goal` is the amount the round itself raises.
+/// In campaigns with tranches each tranche keeps its own rate, so `i_rate` must be 0.
+#[derive(Clone)]
+#[contracttype]
+pub struct RoundParams {
+    pub goal: i128,
+    pub i_rate: u32,
+    pub claim_block_days: u64,
+    pub return_type: u32,
+    pub return_months: u32,
+    pub min_per_investment: i128,
+}
+
+/// A tranche of the campaign with its own interest rate and the most it can raise (0 means no limit).
+/// Tranches with a lower `seniority` rank first: they are paid first in shortfalls and recoveries.
+#[derive(Clone)]
+#[contracttype]
+pub struct Tranche {
+    pub i_rate: u32,
+    pub cap: i128,
+    pub seniority: u32,
+}
+
+/// Campaign parameters that can be updated after deployment
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+#[contracttype]
+pub enum ContractParameter {
+    Goal = 1,
+    MinPerInvestment = 2,
+    ClaimBlockDays = 3,
+}
+
+/// A project address change waiting for its delay to elapse
+#[derive(Clone)]
+#[contracttype]
+pub struct PendingProjectAddress {
+    pub address: Address,
+    pub effective_ts: u64,
+}
+
+/// Where a verified token surplus is swept to
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+#[contracttype]
+pub enum SweepTarget {
+    Reserve = 1,
+    Project = 2,
+    Commission = 3,
+    Owner = 4,
+}
+
 #[contractevent(topics = ["STUPDATED"])]
 pub struct ContractStateUpdated {
     pub new_state: State,
@@ -37,6 +102,13 @@ pub struct InvestmentContractParams {
     pub return_type: u32,
     pub return_months: u32,
     pub min_per_investment: i128,
+    pub max_per_investment: i128,
+    pub max_per_investor: i128,
+    pub max_investors: u32,
+    pub funding_start_ts: u64,
+    pub early_access_secs: u64,
+    pub tranches: Vec<Tranche>,
+    pub revenue_cap_bps: u32,
 }
 
 #[contracttype]
@@ -49,7 +121,22 @@ pub struct ContractData {
     pub return_type: InvestmentReturnType,
     pub return_months: u32,
     pub min_per_investment: i128,
+    pub max_per_investment: i128,
+    pub max_per_investor: i128,
+    pub max_investors: u32,
+    pub funding_start_ts: u64,
+    pub early_access_secs: u64,
     pub goal: i128,
+    pub keeper_bounty_bps: u32,
+    pub shortfall_mode: bool,
+    pub late_penalty_bps: u32,
+    pub late_grace_days: u64,
+    pub auto_top_up: bool,
+    pub auto_top_up_cap: i128,
+    pub round_id: u32,
+    pub referral_share_bps: u32,
+    pub tranches: Vec<Tranche>,
+    pub revenue_cap_bps: u32,
 }
 
 impl ContractData {
@@ -63,13 +150,46 @@ impl ContractData {
             claim_block_days: params.claim_block_days,
             token,
             project_address,
-            state: State::Active,
+            state: match params.funding_start_ts {
+                0 => State::Active,
+                _ => State::Pending,
+            },
             return_type: InvestmentReturnType::from_number(params.return_type).unwrap(),
             return_months: params.return_months,
             min_per_investment: params.min_per_investment,
+            max_per_investment: params.max_per_investment,
+            max_per_investor: params.max_per_investor,
+            max_investors: params.max_investors,
+            funding_start_ts: params.funding_start_ts,
+            early_access_secs: params.early_access_secs,
             goal: params.goal,
+            keeper_bounty_bps: 0,
+            shortfall_mode: false,
+            late_penalty_bps: 0,
+            late_grace_days: 0,
+            auto_top_up: false,
+            auto_top_up_cap: 0,
+            round_id: 1,
+            referral_share_bps: 0,
+            tranches: params.tranches.clone(),
+            revenue_cap_bps: params.revenue_cap_bps,
         }
     }
+
+    /// Returns the interest rate of `tranche`, which takes precedence over the campaign rate.
+    /// Campaigns without tranches use the campaign rate.
+    pub fn tranche_rate(&self, tranche: u32) -> u32 {
+        self.tranches
+            .get(tranche)
+            .map_or(self.interest_rate, |tranche| tranche.i_rate)
+    }
+
+    /// Returns the seniority of `tranche`; every investment ranks equally in campaigns without tranches
+    pub fn tranche_seniority(&self, tranche: u32) -> u32 {
+        self.tranches
+            .get(tranche)
+            .map_or(0, |tranche| tranche.seniority)
+    }
 }
 
 #[derive(Clone)]
@@ -80,4 +200,25 @@ pub enum DataKey {
     ClaimsMap,
     MultisigRequest,
     ContractBalances,
+    PayoutAddress(u32),
+    ClaimDelegate(Address, Address),
+    AllowlistConfig,
+    Allowlisted(Address),
+    InvestorTotal(Address),
+    InvestorsCount,
+    ComplianceOfficer,
+    FrozenPosition(u32),
+    OperationPaused(Operation),
+    RecoveryPool,
+    RecoveryClaimed(u32),
+    PendingProjectAddress,
+    BonusSchedule,
+    ReferralRewards(Address),
+    TrancheRaised(u32, u32),
+    TrancheArrears(u32),
+    RevenuePool,
+    RevenueCheckpoint(u32),
+    RevenueExit(u32),
+    Distribution,
+    DistributionCheckpoint(u32),
 }
@@ -0,0 +1,56 @@
+use soroban_sdk::contracttype;
+
+/// Fixed-point precision of the rewards accumulated per unit of weight
+const REWARD_PRECISION: i128 = 1_000_000_000_000_000_000;
+
+/// Ad-hoc distributions (bonuses, surplus returns) shared by every position by `deposited` weight.
+///
+/// Each distribution adds its amount divided by the total weight to `reward_per_share`, and every
+/// position keeps a checkpoint of `reward_per_share` as of its last claim.
+#[derive(Default)]
+#[contracttype]
+pub struct Distribution {
+    pub reward_per_share: i128,
+    pub total_weight: i128,
+    pub distributed: i128,
+    pub claimed: i128,
+}
+
+impl Distribution {
+    /// Spreads `amount` over the current weight
+    pub fn distribute(&mut self, amount: i128) {
+        self.reward_per_share = accumulate(self.reward_per_share, amount, self.total_weight);
+        self.distributed += amount;
+    }
+
+    /// Returns what a position of `weight` is owed since `checkpoint`
+    pub fn pending(&self, weight: i128, checkpoint: i128) -> i128 {
+        earned(weight, self.reward_per_share, checkpoint)
+    }
+}
+
+/// Returns `reward_per_share` after spreading `amount` over `total_weight`.
+///
+/// Together with `earned`, this lets a payout be shared by weight without visiting every position:
+/// distributing and claiming cost the same however many positions there are.
+pub fn accumulate(reward_per_share: i128, amount: i128, total_weight: i128) -> i128 {
+    reward_per_share + amount * REWARD_PRECISION / total_weight
+}
+
+/// Returns what `weight` earned while the accumulator grew from `checkpoint` to `reward_per_share`
+pub fn earned(weight: i128, reward_per_share: i128, checkpoint: i128) -> i128 {
+    weight * (reward_per_share - checkpoint) / REWARD_PRECISION
+}
+
+/// Returns the value of the accumulator at which `weight` will have earned `amount` since `reward_per_share`, rounded up
+pub fn reward_per_share_for(reward_per_share: i128, amount: i128, weight: i128) -> i128 {
+    reward_per_share + (amount * REWARD_PRECISION + weight - 1) / weight
+}
+
+/// Returns the amount that grows the accumulator from `reward_per_share` to `target` over `total_weight`,
+/// rounded up, or `None` if it doesn't fit in an `i128`
+pub fn amount_to_reach(reward_per_share: i128, target: i128, total_weight: i128) -> Option<i128> {
+    (target - reward_per_share)
+        .checked_mul(total_weight)
+        .map(|scaled| (scaled + REWARD_PRECISION - 1) / REWARD_PRECISION)
+}
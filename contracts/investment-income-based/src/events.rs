@@ -0,0 +1,302 @@
+use soroban_sdk::{contractevent, contracttype, Address, Env};
+use stellar_tokens::non_fungible::Base;
+
+use crate::data::{ContractParameter, Operation, SweepTarget};
+use crate::investment::{Investment, InvestmentReturnType};
+
+/// Identifies who triggered an investor payment
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+#[contracttype]
+pub enum PaymentTrigger {
+    Admin = 1,
+    SelfClaim = 2,
+    Delegate = 3,
+    Keeper = 4,
+}
+
+#[contractevent(topics = ["INVESTED"])]
+pub struct InvestmentCreated {
+    #[topic]
+    pub investor: Address,
+    #[topic]
+    pub token_id: u32,
+    pub amount: i128,
+    pub amount_to_invest: i128,
+    pub amount_to_reserve_fund: i128,
+    pub amount_to_commission: i128,
+    pub interest_rate: u32,
+}
+
+#[contractevent(topics = ["PAYMENT"])]
+pub struct PaymentProcessed {
+    #[topic]
+    pub token_id: u32,
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+    pub periods: u32,
+    pub trigger: PaymentTrigger,
+}
+
+#[contractevent(topics = ["WITHDRAWN"])]
+pub struct ProjectWithdrawal {
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["RSVCONTRB"])]
+pub struct ReserveContribution {
+    #[topic]
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["RSVMOVED"])]
+pub struct ReserveMove {
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["RSVTOPUP"])]
+pub struct ReserveAutoTopUp {
+    pub amount: i128,
+    pub amount_needed: i128,
+}
+
+#[contractevent(topics = ["SWEPT"])]
+pub struct SurplusSwept {
+    #[topic]
+    pub target: SweepTarget,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["RESCUED"])]
+pub struct TokenRescued {
+    #[topic]
+    pub token: Address,
+    #[topic]
+    pub to: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["PARAMSET"])]
+pub struct ParameterUpdated {
+    #[topic]
+    pub parameter: ContractParameter,
+    pub old_value: i128,
+    pub new_value: i128,
+}
+
+#[contractevent(topics = ["PROJPROP"])]
+pub struct ProjectAddressChangeProposed {
+    #[topic]
+    pub project_address: Address,
+    pub effective_ts: u64,
+}
+
+#[contractevent(topics = ["PROJADDR"])]
+pub struct ProjectAddressUpdated {
+    #[topic]
+    pub project_address: Address,
+    pub effective_ts: u64,
+}
+
+#[contractevent(topics = ["ROUNDOPEN"])]
+pub struct RoundOpened {
+    #[topic]
+    pub round_id: u32,
+    pub goal: i128,
+    pub interest_rate: u32,
+    pub claim_block_days: u64,
+    pub return_type: InvestmentReturnType,
+    pub return_months: u32,
+    pub min_per_investment: i128,
+}
+
+#[contractevent(topics = ["INVFINISH"])]
+pub struct InvestmentFinished {
+    #[topic]
+    pub token_id: u32,
+    #[topic]
+    pub investor: Address,
+    pub total_paid: i128,
+}
+
+#[contractevent(topics = ["PAYOUTSET"])]
+pub struct PayoutAddressUpdated {
+    #[topic]
+    pub token_id: u32,
+    pub payout_address: Option<Address>,
+}
+
+#[contractevent(topics = ["DELEGATE"])]
+pub struct ClaimDelegateUpdated {
+    #[topic]
+    pub owner: Address,
+    #[topic]
+    pub delegate: Address,
+    pub approved: bool,
+}
+
+#[contractevent(topics = ["KEEPERRWD"])]
+pub struct KeeperRewarded {
+    #[topic]
+    pub keeper: Address,
+    #[topic]
+    pub token_id: u32,
+    pub bounty: i128,
+}
+
+#[contractevent(topics = ["ALLOWLIST"])]
+pub struct AllowlistUpdated {
+    #[topic]
+    pub investor: Address,
+    pub allowed: bool,
+}
+
+#[contractevent(topics = ["ALLOWCFG"])]
+pub struct AllowlistConfigUpdated {
+    pub enabled: bool,
+    pub verifier: Option<Address>,
+}
+
+#[contractevent(topics = ["COMPLIANC"])]
+pub struct ComplianceOfficerUpdated {
+    pub officer: Option<Address>,
+}
+
+#[contractevent(topics = ["FREEZE"])]
+pub struct PositionFreezeUpdated {
+    #[topic]
+    pub token_id: u32,
+    #[topic]
+    pub caller: Address,
+    pub frozen: bool,
+}
+
+#[contractevent(topics = ["SEIZED"])]
+pub struct PositionSeized {
+    #[topic]
+    pub token_id: u32,
+    #[topic]
+    pub caller: Address,
+    pub from: Address,
+    pub to: Address,
+}
+
+#[contractevent(topics = ["OPPAUSED"])]
+pub struct OperationPauseUpdated {
+    #[topic]
+    pub operation: Operation,
+    pub paused: bool,
+}
+
+#[contractevent(topics = ["RECDEPOSIT"])]
+pub struct RecoveryDeposited {
+    #[topic]
+    pub from: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["RECCLAIMED"])]
+pub struct RecoveryClaimed {
+    #[topic]
+    pub token_id: u32,
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["REFERRAL"])]
+pub struct ReferralCredited {
+    #[topic]
+    pub referrer: Address,
+    #[topic]
+    pub token_id: u32,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["REFCLAIMED"])]
+pub struct ReferralRewardsClaimed {
+    #[topic]
+    pub referrer: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["REVENUE"])]
+pub struct RevenueReported {
+    #[topic]
+    pub period: u32,
+    pub amount: i128,
+    pub reward_per_share: i128,
+}
+
+#[contractevent(topics = ["DISTRIB"])]
+pub struct Distributed {
+    pub amount: i128,
+    pub reward_per_share: i128,
+}
+
+#[contractevent(topics = ["DISTCLAIM"])]
+pub struct DistributionClaimed {
+    #[topic]
+    pub token_id: u32,
+    #[topic]
+    pub recipient: Address,
+    pub amount: i128,
+}
+
+#[contractevent(topics = ["SHORTFALL"])]
+pub struct PaymentShortfall {
+    #[topic]
+    pub token_id: u32,
+    pub amount_paid: i128,
+    pub arrears: i128,
+}
+
+#[contractevent(topics = ["PENALTY"])]
+pub struct LatePenaltyPaid {
+    #[topic]
+    pub token_id: u32,
+    pub amount: i128,
+}
+
+/// Emits a LatePenaltyPaid event when a payment included late-payment penalty
+pub fn emit_penalty_event(env: &Env, token_id: u32, penalty: i128) {
+    if penalty > 0 {
+        LatePenaltyPaid {
+            token_id,
+            amount: penalty,
+        }
+        .publish(env);
+    }
+}
+
+/// Emits a PaymentProcessed event, followed by an InvestmentFinished event when it settled the investment
+pub fn emit_payment_events(
+    env: &Env,
+    investment: &Investment,
+    recipient: &Address,
+    amount: i128,
+    periods: u32,
+    trigger: PaymentTrigger,
+) {
+    PaymentProcessed {
+        token_id: investment.token_id,
+        recipient: recipient.clone(),
+        amount,
+        periods,
+        trigger,
+    }
+    .publish(env);
+
+    if investment.is_settled() {
+        InvestmentFinished {
+            token_id: investment.token_id,
+            investor: Base::owner_of(env, investment.token_id),
+            total_paid: investment.paid,
+        }
+        .publish(env);
+    }
+}
@@ -0,0 +1,109 @@
+use soroban_sdk::{contracttype, Env, Map, Vec};
+
+use crate::balance::BPS_DENOMINATOR;
+use crate::claim::Claim;
+use crate::constants::SECONDS_IN_MONTH;
+use crate::investment::{Investment, InvestmentReturnType};
+use crate::storage as Storage;
+
+/// Total due within a period of the forecast, from `start_ts` (inclusive) to `end_ts` (exclusive)
+#[contracttype]
+pub struct ObligationBucket {
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub amount: i128,
+}
+
+/// Obligations due within a time range compared with the current reserve.
+///
+/// `coverage_bps` is the reserve over the total due in basis points, or `None` when nothing is due.
+#[contracttype]
+pub struct ObligationsForecast {
+    pub buckets: Vec<ObligationBucket>,
+    pub total_due: i128,
+    pub reserve: i128,
+    pub coverage_bps: Option<i128>,
+}
+
+/// Forecasts the payments due from `from_ts` to `to_ts` across all the active investments, in one-period buckets.
+///
+/// Every remaining installment is counted, including the Coupon principal at maturity. Arrears, accrued
+/// penalties and installments already due at `from_ts` fall in the first bucket.
+pub fn forecast_obligations(
+    env: &Env,
+    from_ts: u64,
+    to_ts: u64,
+    reserve: i128,
+) -> ObligationsForecast {
+    let mut buckets: Vec<ObligationBucket> = Vec::new(env);
+    let mut start_ts = from_ts;
+    while start_ts < to_ts {
+        let end_ts = (start_ts + SECONDS_IN_MONTH).min(to_ts);
+        buckets.push_back(ObligationBucket {
+            start_ts,
+            end_ts,
+            amount: 0,
+        });
+        start_ts = end_ts;
+    }
+
+    let claims_map: Map<u32, Claim> = Storage::get_claims_map_or_new(env);
+    for token_id in claims_map.keys().iter() {
+        if let Some(investment) = Storage::get_investment(env, token_id) {
+            if !investment.is_settled() {
+                add_obligations(&mut buckets, from_ts, to_ts, &investment);
+            }
+        }
+    }
+
+    let total_due: i128 = buckets.iter().map(|bucket| bucket.amount).sum();
+    ObligationsForecast {
+        buckets,
+        total_due,
+        reserve,
+        coverage_bps: match total_due {
+            0 => None,
+            _ => Some(reserve * BPS_DENOMINATOR / total_due),
+        },
+    }
+}
+
+fn add_obligations(
+    buckets: &mut Vec<ObligationBucket>,
+    from_ts: u64,
+    to_ts: u64,
+    investment: &Investment,
+) {
+    add_to_bucket(buckets, from_ts, from_ts, investment.arrears + investment.penalty);
+
+    let remaining = investment
+        .return_months
+        .saturating_sub(investment.payments_transferred);
+    let next_due_ts = investment.next_due_ts();
+
+    for payment in 0..remaining {
+        let due_ts = (next_due_ts + payment as u64 * SECONDS_IN_MONTH).max(from_ts);
+        if due_ts >= to_ts {
+            break;
+        }
+
+        let mut amount = investment.regular_payment;
+        if payment + 1 == remaining && investment.return_type == InvestmentReturnType::Coupon {
+            amount += investment.deposited;
+        }
+        add_to_bucket(buckets, from_ts, due_ts, amount);
+    }
+}
+
+/// Adds `amount` to the bucket `due_ts` falls in; buckets are one period long starting at `from_ts`
+fn add_to_bucket(buckets: &mut Vec<ObligationBucket>, from_ts: u64, due_ts: u64, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+
+    let index = ((due_ts - from_ts) / SECONDS_IN_MONTH) as u32;
+    if let Some(mut bucket) = buckets.get(index) {
+        bucket.amount += amount;
+        buckets.set(index, bucket);
+    }
+}
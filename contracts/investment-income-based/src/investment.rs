@@ -1,6 +1,6 @@
 use crate::{
-    balance::{Amount, CalculateAmounts},
-    constants::SECONDS_IN_DAY,
+    balance::{calculate_late_penalty, Amount, CalculateAmounts, BPS_DENOMINATOR},
+    constants::{SECONDS_IN_DAY, SECONDS_IN_MONTH},
     data::{ContractData, FromNumber},
 };
 use soroban_sdk::{contracttype, Env};
@@ -19,13 +19,38 @@ pub struct Investment {
     pub paid: i128,
     pub payments_transferred: u32,
     pub token_id: u32,
+    pub arrears: i128,
+    pub arrears_ts: u64,
+    pub penalty: i128,
+    pub round_id: u32,
+    pub return_type: InvestmentReturnType,
+    pub return_months: u32,
+    pub interest_rate: u32,
+    pub tranche: u32,
 }
 
 impl Investment {
-    pub fn new(env: &Env, cd: &ContractData, amount: &i128, decimals: u8, token_id: u32) -> Self {
-        let amounts: Amount = Amount::from_investment(env, amount, &cd.interest_rate, decimals);
+    /// Creates an investment of `amount` in `tranche`, earning the interest rate of the tranche plus
+    /// `bonus_bps`. The commission is always computed on the tranche rate, without the bonus.
+    pub fn new(
+        env: &Env,
+        cd: &ContractData,
+        tranche: u32,
+        bonus_bps: u32,
+        amount: &i128,
+        decimals: u8,
+        token_id: u32,
+    ) -> Self {
+        let tranche_rate = cd.tranche_rate(tranche);
+        let amounts: Amount = Amount::from_investment(env, amount, &tranche_rate, decimals);
         let real_amount = amounts.amount_to_invest + amounts.amount_to_reserve_fund;
-        let current_interest = (real_amount * cd.interest_rate as i128) / 100 / 100;
+        let interest_rate = tranche_rate + bonus_bps;
+        let current_interest = match cd.return_type {
+            InvestmentReturnType::RevenueShare => {
+                real_amount * cd.revenue_cap_bps as i128 / BPS_DENOMINATOR - real_amount
+            }
+            _ => (real_amount * interest_rate as i128) / 100 / 100,
+        };
         let total_gains = real_amount + current_interest;
 
         let status = Self::calculate_initial_status(&cd.claim_block_days);
@@ -49,10 +74,18 @@ impl Investment {
             paid: 0_i128,
             payments_transferred: 0_u32,
             token_id,
+            arrears: 0_i128,
+            arrears_ts: 0_u64,
+            penalty: 0_i128,
+            round_id: cd.round_id,
+            return_type: cd.return_type,
+            return_months: cd.return_months,
+            interest_rate,
+            tranche,
         }
     }
 
-    pub fn process_investment_payment(&mut self, env: &Env, contract_data: &ContractData) -> i128 {
+    pub fn process_investment_payment(&mut self, env: &Env) -> i128 {
         let mut amount_to_transfer: i128;
         if self.status != InvestmentStatus::CashFlowing {
             self.status = InvestmentStatus::CashFlowing;
@@ -63,12 +96,12 @@ impl Investment {
         self.payments_transferred += 1;
         amount_to_transfer = self.regular_payment;
 
-        let is_last_payment = self.payments_transferred >= contract_data.return_months;
+        let is_last_payment = self.payments_transferred >= self.return_months;
 
         if is_last_payment {
             self.status = InvestmentStatus::Finished;
 
-            if contract_data.return_type == InvestmentReturnType::Coupon {
+            if self.return_type == InvestmentReturnType::Coupon {
                 self.paid += self.deposited;
                 amount_to_transfer += self.deposited;
             }
@@ -77,6 +110,84 @@ impl Investment {
         amount_to_transfer
     }
 
+    /// Records a revenue-share payment of `amount`, finishing the position once it has been paid its cap
+    pub fn process_revenue_payment(&mut self, env: &Env, amount: i128) {
+        self.paid += amount;
+        self.last_transfer_ts = env.ledger().timestamp();
+        self.payments_transferred += 1;
+        self.status = match self.paid >= self.total {
+            true => InvestmentStatus::Finished,
+            false => InvestmentStatus::CashFlowing,
+        };
+    }
+
+    /// Marks the arrears left by previous partial payments as paid and returns their amount
+    pub fn settle_arrears(&mut self) -> i128 {
+        let arrears = self.arrears;
+        self.paid += arrears;
+        self.arrears = 0;
+        arrears
+    }
+
+    /// Records the part of a processed payment that could not be transferred as arrears
+    pub fn record_shortfall(&mut self, env: &Env, unpaid: i128) {
+        if unpaid > 0 {
+            self.paid -= unpaid;
+            self.arrears += unpaid;
+            self.arrears_ts = env.ledger().timestamp();
+        }
+    }
+
+    /// Accrues the late-payment penalty of the arrears, which are about to be settled.
+    ///
+    /// Only a recorded shortfall is late: installments the reserve could cover are not, however long
+    /// the investor waited to claim them. The arrears accrue `late_penalty_bps` of their amount for every
+    /// full period past the grace window since the partial payment that left them.
+    pub fn accrue_late_penalty(&mut self, env: &Env, contract_data: &ContractData) {
+        if contract_data.late_penalty_bps == 0 || self.arrears == 0 {
+            return;
+        }
+
+        let grace = contract_data.late_grace_days.saturating_mul(SECONDS_IN_DAY);
+        let overdue = self.arrears * late_periods(env, self.arrears_ts, grace);
+        self.penalty += calculate_late_penalty(overdue, contract_data.late_penalty_bps);
+    }
+
+    /// Returns when the next installment falls due: the claimable date, or one period after the last transfer
+    pub fn next_due_ts(&self) -> u64 {
+        match self.last_transfer_ts {
+            0 => self.claimable_ts,
+            lts => lts + SECONDS_IN_MONTH,
+        }
+    }
+
+    /// Marks the accrued late-payment penalty as paid and returns its amount
+    pub fn settle_penalty(&mut self) -> i128 {
+        let penalty = self.penalty;
+        self.penalty = 0;
+        penalty
+    }
+
+    /// Returns whether arrears or late-payment penalties are pending
+    pub fn has_arrears(&self) -> bool {
+        self.arrears > 0 || self.penalty > 0
+    }
+
+    /// Settles the arrears and then processes `num_payments` installments, returning the total amount to transfer
+    pub fn process_claim(&mut self, env: &Env, num_payments: u32) -> i128 {
+        let mut amount_to_transfer = self.settle_arrears();
+        if num_payments > 0 {
+            amount_to_transfer += self.process_multiple_payments(env, num_payments);
+        }
+
+        amount_to_transfer
+    }
+
+    /// Returns whether every installment has been processed and nothing is left in arrears
+    pub fn is_settled(&self) -> bool {
+        self.status == InvestmentStatus::Finished && !self.has_arrears()
+    }
+
     fn calculate_initial_status(claim_block_days: &u64) -> InvestmentStatus {
         let status: InvestmentStatus = match claim_block_days {
             claim_block_days if *claim_block_days > 0 => InvestmentStatus::Blocked,
@@ -98,15 +209,11 @@ impl Investment {
         match return_type {
             InvestmentReturnType::Coupon => interest_gains / *return_months as i128,
             InvestmentReturnType::ReverseLoan => total_gains / *return_months as i128,
+            InvestmentReturnType::RevenueShare => 0,
         }
     }
 
-    pub fn process_multiple_payments(
-        &mut self,
-        env: &Env,
-        contract_data: &ContractData,
-        num_payments: u32,
-    ) -> i128 {
+    pub fn process_multiple_payments(&mut self, env: &Env, num_payments: u32) -> i128 {
         if self.status != InvestmentStatus::CashFlowing {
             self.status = InvestmentStatus::CashFlowing;
         }
@@ -116,12 +223,12 @@ impl Investment {
         self.last_transfer_ts = env.ledger().timestamp();
         self.payments_transferred += num_payments;
 
-        let is_last_payment = self.payments_transferred >= contract_data.return_months;
+        let is_last_payment = self.payments_transferred >= self.return_months;
 
         if is_last_payment {
             self.status = InvestmentStatus::Finished;
 
-            if contract_data.return_type == InvestmentReturnType::Coupon {
+            if self.return_type == InvestmentReturnType::Coupon {
                 self.paid += self.deposited;
                 total_amount += self.deposited;
             }
@@ -131,6 +238,17 @@ impl Investment {
     }
 }
 
+/// Returns the full periods elapsed since `due_ts` plus the grace window
+fn late_periods(env: &Env, due_ts: u64, grace: u64) -> i128 {
+    let late_ts = due_ts.saturating_add(grace);
+    let now = env.ledger().timestamp();
+    if now <= late_ts {
+        return 0;
+    }
+
+    ((now - late_ts) / SECONDS_IN_MONTH) as i128
+}
+
 #[derive(Copy, Clone, PartialEq, Debug)]
 #[repr(u32)]
 #[contracttype]
@@ -141,12 +259,13 @@ pub enum InvestmentStatus {
     Finished = 5,
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(u32)]
 #[contracttype]
 pub enum InvestmentReturnType {
     ReverseLoan = 1,
     Coupon = 2,
+    RevenueShare = 3,
 }
 
 impl FromNumber for InvestmentReturnType {
@@ -158,6 +277,7 @@ impl FromNumber for InvestmentReturnType {
         match value {
             1 => Some(InvestmentReturnType::ReverseLoan),
             2 => Some(InvestmentReturnType::Coupon),
+            3 => Some(InvestmentReturnType::RevenueShare),
             _ => None,
         }
     }
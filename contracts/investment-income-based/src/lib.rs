@@ -1,10 +1,18 @@
 #![no_std]
 
+pub mod allowlist;
 pub mod balance;
+pub mod bonus;
 mod claim;
 mod constants;
 pub mod contract;
 pub mod data;
+pub mod distribution;
+pub mod events;
+pub mod forecast;
 pub mod investment;
+pub mod payment;
+pub mod recovery;
+pub mod revenue;
 mod storage;
 mod validation;
@@ -0,0 +1,252 @@
+use soroban_sdk::{contracttype, Address, Env, Map, Vec};
+use stellar_tokens::non_fungible::Base;
+
+use crate::claim::calculate_claimable_payments;
+use crate::data::ContractData;
+use crate::investment::Investment;
+use crate::{storage as Storage, validation};
+
+/// Outcome of a batch of investor payments
+#[contracttype]
+pub struct BatchPaymentSummary {
+    pub paid: Vec<u32>,
+    pub skipped: Vec<u32>,
+    pub total_paid: i128,
+    pub next_cursor: Option<u32>,
+}
+
+/// A payment already applied to its investment but not transferred yet
+#[contracttype]
+pub struct DuePayment {
+    pub recipient: Address,
+    pub investment: Investment,
+    pub amount: i128,
+    pub arrears: i128,
+    pub penalty: i128,
+    pub periods: u32,
+    pub seniority: u32,
+}
+
+/// Computes the payment of every due investment in `token_ids`, arrears included.
+///
+/// Returns the due payments keyed by token id and the token ids that were skipped because
+/// they do not exist, are frozen, are repeated, have neither a due installment nor arrears, or rank
+/// below a tranche still owed arrears that the batch itself doesn't settle.
+pub fn collect_due_payments(
+    env: &Env,
+    contract_data: &ContractData,
+    token_ids: &Vec<u32>,
+) -> (Map<u32, DuePayment>, Vec<u32>) {
+    let mut due_payments: Map<u32, DuePayment> = Map::new(env);
+    let mut skipped: Vec<u32> = Vec::new(env);
+
+    for token_id in token_ids.iter() {
+        let mut investment = match Storage::get_investment(env, token_id) {
+            Some(inv)
+                if !due_payments.contains_key(token_id)
+                    && validation::validate_position_not_frozen(env, token_id).is_ok() =>
+            {
+                inv
+            }
+            _ => {
+                skipped.push_back(token_id);
+                continue;
+            }
+        };
+
+        let installment_due = validation::validate_investment_payment(env, &investment).is_ok();
+        if !installment_due && !investment.has_arrears() {
+            skipped.push_back(token_id);
+            continue;
+        }
+
+        investment.accrue_late_penalty(env, contract_data);
+        let penalty = investment.settle_penalty();
+        let arrears = investment.settle_arrears();
+        let mut amount = penalty + arrears;
+        if installment_due {
+            amount += investment.process_investment_payment(env);
+        }
+
+        due_payments.set(
+            token_id,
+            DuePayment {
+                recipient: payout_recipient(env, token_id, &Base::owner_of(env, token_id)),
+                seniority: contract_data.tranche_seniority(investment.tranche),
+                investment,
+                amount,
+                arrears,
+                penalty,
+                periods: installment_due as u32,
+            },
+        );
+    }
+
+    let mut settled: Map<u32, i128> = Map::new(env);
+    for due in due_payments.values().iter() {
+        let tranche = due.investment.tranche;
+        settled.set(tranche, settled.get(tranche).unwrap_or(0) + due.arrears);
+    }
+    for token_id in due_payments.keys().iter() {
+        let tranche = due_payments.get_unchecked(token_id).investment.tranche;
+        if validation::validate_senior_arrears_covered(env, contract_data, tranche, &settled).is_err() {
+            due_payments.remove(token_id);
+            skipped.push_back(token_id);
+        }
+    }
+
+    (due_payments, skipped)
+}
+
+/// Scales the due payments down so they fit in `available`, following the tranche waterfall.
+///
+/// Late-payment penalties are deferred to a later payout. Payments are grouped by seniority and each
+/// group is paid in full before the next one receives anything, so junior tranches absorb the shortfall.
+/// The unpaid part of each payment is recorded as arrears on its investment.
+pub fn apply_shortfall(
+    env: &Env,
+    due_payments: &Map<u32, DuePayment>,
+    available: i128,
+) -> Map<u32, DuePayment> {
+    let mut levels: Map<u32, Map<u32, DuePayment>> = Map::new(env);
+    for (token_id, due) in due_payments.iter() {
+        let seniority = due.seniority;
+        let mut level = levels.get(seniority).unwrap_or_else(|| Map::new(env));
+        level.set(token_id, due);
+        levels.set(seniority, level);
+    }
+
+    let mut remaining = available;
+    let mut scaled: Map<u32, DuePayment> = Map::new(env);
+    for level in levels.values().iter() {
+        for (token_id, due) in scale_level(env, &level, remaining).iter() {
+            remaining -= due.amount;
+            scaled.set(token_id, due);
+        }
+    }
+
+    scaled
+}
+
+/// Scales the due payments of a seniority level down so they fit in `available`, settling arrears first.
+///
+/// Arrears are paid in full when `available` covers them and what is left is shared among the
+/// installments in proportion to their amount. Otherwise the arrears themselves are paid pro-rata
+/// and no installment is paid.
+fn scale_level(
+    env: &Env,
+    due_payments: &Map<u32, DuePayment>,
+    available: i128,
+) -> Map<u32, DuePayment> {
+    let total_arrears: i128 = due_payments.values().iter().map(|due| due.arrears).sum();
+    let total_penalties: i128 = due_payments.values().iter().map(|due| due.penalty).sum();
+    let total_installments = total_due(due_payments) - total_arrears - total_penalties;
+    let mut scaled: Map<u32, DuePayment> = Map::new(env);
+
+    for (token_id, mut due) in due_payments.iter() {
+        due.investment.penalty += due.penalty;
+        due.amount -= due.penalty;
+        due.penalty = 0;
+
+        let amount = if available >= total_arrears {
+            let installment = due.amount - due.arrears;
+            due.arrears + pro_rata(installment, available - total_arrears, total_installments)
+        } else {
+            pro_rata(due.arrears, available, total_arrears)
+        };
+
+        due.investment.record_shortfall(env, due.amount - amount);
+        due.amount = amount;
+        scaled.set(token_id, due);
+    }
+
+    scaled
+}
+
+fn pro_rata(amount: i128, numerator: i128, denominator: i128) -> i128 {
+    if denominator <= 0 {
+        return 0;
+    }
+
+    (amount * numerator / denominator).min(amount)
+}
+
+/// Computes the accumulated claimable payments of every investment in `token_ids` owned by `owner`, arrears included.
+///
+/// Returns the claimable payments keyed by token id and the token ids that were skipped because
+/// they do not exist, are not claimable yet, are settled, have no elapsed periods nor arrears, rank below a
+/// tranche still owed arrears or are repeated.
+pub fn collect_claimable_payments(
+    env: &Env,
+    contract_data: &ContractData,
+    owner: &Address,
+    token_ids: &Vec<u32>,
+) -> (Map<u32, DuePayment>, Vec<u32>) {
+    let mut claimable: Map<u32, DuePayment> = Map::new(env);
+    let mut skipped: Vec<u32> = Vec::new(env);
+
+    for token_id in token_ids.iter() {
+        let mut investment = match Storage::get_investment(env, token_id) {
+            Some(inv) if !claimable.contains_key(token_id) => inv,
+            _ => {
+                skipped.push_back(token_id);
+                continue;
+            }
+        };
+
+        if validation::validate_claim(env, &investment).is_err()
+            || validation::validate_senior_arrears_settled(env, contract_data, investment.tranche).is_err()
+        {
+            skipped.push_back(token_id);
+            continue;
+        }
+
+        let num_payments = calculate_claimable_payments(env, &investment);
+        if num_payments == 0 && !investment.has_arrears() {
+            skipped.push_back(token_id);
+            continue;
+        }
+
+        let arrears = investment.arrears;
+        investment.accrue_late_penalty(env, contract_data);
+        let penalty = investment.settle_penalty();
+        let amount = penalty + investment.process_claim(env, num_payments);
+        claimable.set(
+            token_id,
+            DuePayment {
+                recipient: payout_recipient(env, token_id, owner),
+                seniority: contract_data.tranche_seniority(investment.tranche),
+                investment,
+                amount,
+                arrears,
+                penalty,
+                periods: num_payments,
+            },
+        );
+    }
+
+    (claimable, skipped)
+}
+
+pub fn total_due(due_payments: &Map<u32, DuePayment>) -> i128 {
+    due_payments.values().iter().map(|due| due.amount).sum()
+}
+
+pub fn total_penalties(due_payments: &Map<u32, DuePayment>) -> i128 {
+    due_payments.values().iter().map(|due| due.penalty).sum()
+}
+
+/// Adds up the due payments per recipient so each recipient gets a single transfer
+pub fn totals_by_recipient(env: &Env, due_payments: &Map<u32, DuePayment>) -> Map<Address, i128> {
+    let mut totals: Map<Address, i128> = Map::new(env);
+    for due in due_payments.values().iter() {
+        let total = totals.get(due.recipient.clone()).unwrap_or(0) + due.amount;
+        totals.set(due.recipient, total);
+    }
+    totals
+}
+
+/// Returns the payout address of a position, falling back to its owner
+pub fn payout_recipient(env: &Env, token_id: u32, owner: &Address) -> Address {
+    Storage::get_payout_address(env, token_id).unwrap_or_else(|| owner.clone())
+}
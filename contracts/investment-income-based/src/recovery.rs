@@ -0,0 +1,80 @@
+use soroban_sdk::{contracttype, Env, Map};
+
+use crate::claim::Claim;
+use crate::data::ContractData;
+use crate::investment::{Investment, InvestmentReturnType, InvestmentStatus};
+use crate::storage as Storage;
+
+/// Funds recovered after a project default, shared among the outstanding positions following the tranche waterfall
+#[contracttype]
+pub struct RecoveryPool {
+    pub outstanding: i128,
+    pub deposited: i128,
+    pub distributed: i128,
+    pub outstanding_by_seniority: Map<u32, i128>,
+}
+
+impl RecoveryPool {
+    pub fn new(env: &Env) -> Self {
+        RecoveryPool {
+            outstanding: 0,
+            deposited: 0,
+            distributed: 0,
+            outstanding_by_seniority: Map::new(env),
+        }
+    }
+
+    /// Returns the part of the recovered funds a position is entitled to, given its outstanding balance
+    /// and the seniority of its tranche.
+    ///
+    /// The recovered funds cover the outstanding balance of every more senior level first. What is left,
+    /// up to the outstanding balance of the position's level, is shared pro-rata within the level.
+    /// Positions are never entitled to more than their outstanding balance, even if more funds
+    /// than the total outstanding balance are recovered.
+    pub fn entitlement(&self, position_outstanding: i128, seniority: u32) -> i128 {
+        let level_outstanding = self.outstanding_by_seniority.get(seniority).unwrap_or(0);
+        if level_outstanding == 0 {
+            return 0;
+        }
+
+        let senior_outstanding: i128 = self
+            .outstanding_by_seniority
+            .iter()
+            .filter(|(level, _)| *level < seniority)
+            .map(|(_, outstanding)| outstanding)
+            .sum();
+        let available = (self.deposited - senior_outstanding).clamp(0, level_outstanding);
+
+        available * position_outstanding / level_outstanding
+    }
+}
+
+/// Returns what is still owed to a position, which is only its arrears once it is finished.
+/// Revenue-share positions are only owed the principal they haven't been paid back yet.
+pub fn outstanding_balance(investment: &Investment) -> i128 {
+    if investment.status == InvestmentStatus::Finished {
+        return investment.arrears;
+    }
+
+    if investment.return_type == InvestmentReturnType::RevenueShare {
+        return (investment.deposited - investment.paid).max(0);
+    }
+
+    (investment.total - investment.paid).max(0)
+}
+
+/// Adds up the outstanding balance of every investment per seniority of its tranche
+pub fn outstanding_by_seniority(env: &Env, contract_data: &ContractData) -> Map<u32, i128> {
+    let claims_map: Map<u32, Claim> = Storage::get_claims_map_or_new(env);
+    let mut outstanding: Map<u32, i128> = Map::new(env);
+
+    for token_id in claims_map.keys().iter() {
+        if let Some(investment) = Storage::get_investment(env, token_id) {
+            let seniority = contract_data.tranche_seniority(investment.tranche);
+            let total = outstanding.get(seniority).unwrap_or(0) + outstanding_balance(&investment);
+            outstanding.set(seniority, total);
+        }
+    }
+
+    outstanding
+}
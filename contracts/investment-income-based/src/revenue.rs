@@ -0,0 +1,83 @@
+use soroban_sdk::{contracttype, Env, Map};
+
+use crate::distribution::{accumulate, amount_to_reach, earned, reward_per_share_for};
+
+/// Revenue reported by the project for revenue-share positions.
+///
+/// Every report adds the investors' share, divided by the total weight (the `deposited` amount of
+/// the revenue-share positions still earning), to `reward_per_share`. A position reaches its cap at
+/// a known value of `reward_per_share`, its exit point: from there on its weight no longer counts,
+/// so the rest of a report goes to the positions still earning.
+#[contracttype]
+pub struct RevenuePool {
+    pub reward_per_share: i128,
+    pub total_weight: i128,
+    pub last_period: u32,
+    pub reported: i128,
+    pub distributed: i128,
+    pub exits: Map<i128, i128>,
+}
+
+impl RevenuePool {
+    pub fn new(env: &Env) -> Self {
+        RevenuePool {
+            reward_per_share: 0,
+            total_weight: 0,
+            last_period: 0,
+            reported: 0,
+            distributed: 0,
+            exits: Map::new(env),
+        }
+    }
+
+    /// Adds a position of `weight` that can still earn `cap` and returns its exit point
+    pub fn join(&mut self, weight: i128, cap: i128) -> i128 {
+        let exit = reward_per_share_for(self.reward_per_share, cap, weight);
+        self.exits.set(exit, self.exits.get(exit).unwrap_or(0) + weight);
+        self.total_weight += weight;
+        exit
+    }
+
+    /// Spreads up to `amount` of the revenue of `period` over the positions still earning and returns
+    /// the part distributed.
+    ///
+    /// Positions whose exit point is reached leave the pool and the rest of the amount is spread over
+    /// the remaining ones. Whatever no position can earn anymore is not distributed.
+    pub fn report(&mut self, period: u32, amount: i128) -> i128 {
+        let mut left = amount;
+        while left > 0 && self.total_weight > 0 {
+            let reached = self.exits.keys().first().and_then(|exit| {
+                amount_to_reach(self.reward_per_share, exit, self.total_weight)
+                    .filter(|cost| *cost <= left)
+                    .map(|cost| (exit, cost))
+            });
+
+            match reached {
+                Some((exit, cost)) => {
+                    self.reward_per_share = exit;
+                    self.total_weight -= self.exits.get(exit).unwrap_or(0);
+                    self.exits.remove(exit);
+                    left -= cost;
+                }
+                None => {
+                    self.reward_per_share = accumulate(self.reward_per_share, left, self.total_weight);
+                    left = 0;
+                }
+            }
+        }
+
+        self.last_period = period;
+        self.reported += amount - left;
+        amount - left
+    }
+
+    /// Returns the revenue owed to a position of `weight` since `checkpoint`, which can still earn `remaining`.
+    /// Once the pool has passed its `exit` point, the position is owed all of it.
+    pub fn pending(&self, weight: i128, checkpoint: i128, exit: i128, remaining: i128) -> i128 {
+        if self.reward_per_share >= exit {
+            return remaining;
+        }
+
+        earned(weight, self.reward_per_share, checkpoint).min(remaining)
+    }
+}
@@ -1,10 +1,15 @@
 use crate::{
+    allowlist::AllowlistConfig,
     balance::ContractBalance,
+    bonus::BonusSchedule,
     claim::{calculate_next_claim, Claim},
-    data::{ContractData, DataKey},
-    investment::{Investment, InvestmentStatus},
+    data::{ContractData, DataKey, Operation, PendingProjectAddress},
+    distribution::Distribution,
+    investment::Investment,
+    recovery::RecoveryPool,
+    revenue::RevenuePool,
 };
-use soroban_sdk::{Env, Map};
+use soroban_sdk::{Address, Env, Map};
 
 const DAY_IN_LEDGERS: u32 = 17280;
 
@@ -38,7 +43,7 @@ pub fn get_investment(e: &Env, token_id: u32) -> Option<Investment> {
     let investment: Option<Investment> = e.storage().persistent().get(&key);
 
     if let Some(ref inv) = investment {
-        if inv.status != InvestmentStatus::Finished {
+        if !inv.is_settled() {
             bump_persistent_ttl(e, &key);
         }
     }
@@ -47,6 +52,12 @@ pub fn get_investment(e: &Env, token_id: u32) -> Option<Investment> {
 }
 
 pub fn update_investment_with_claim(e: &Env, token_id: u32, investment: &Investment) {
+    let previous_arrears = get_investment(e, token_id).map_or(0, |inv| inv.arrears);
+    if investment.arrears != previous_arrears {
+        let arrears = get_tranche_arrears(e, investment.tranche) + investment.arrears - previous_arrears;
+        e.storage().instance().set(&DataKey::TrancheArrears(investment.tranche), &arrears);
+    }
+
     set_investment(e, token_id, investment);
     let mut claims_map = get_claims_map_or_new(e);
     claims_map.set(token_id, calculate_next_claim(e, investment));
@@ -61,6 +72,16 @@ pub fn get_claims_map_or_new(e: &Env) -> Map<u32, Claim> {
         .unwrap_or(Map::<u32, Claim>::new(e))
 }
 
+pub fn has_outstanding_investments(e: &Env) -> bool {
+    get_claims_map_or_new(e).keys().iter().any(|token_id| {
+        let investment: Option<Investment> = e
+            .storage()
+            .persistent()
+            .get(&DataKey::Investment(token_id));
+        matches!(investment, Some(inv) if !inv.is_settled())
+    })
+}
+
 pub fn update_contract_balances(e: &Env, contract_balances: &ContractBalance) {
     e.storage()
         .instance()
@@ -73,6 +94,285 @@ pub fn get_balances_or_new(e: &Env) -> ContractBalance {
     e.storage().instance().get(&key).unwrap_or_default()
 }
 
+pub fn get_payout_address(e: &Env, token_id: u32) -> Option<Address> {
+    let key = DataKey::PayoutAddress(token_id);
+    let payout_address: Option<Address> = e.storage().persistent().get(&key);
+    if payout_address.is_some() {
+        bump_persistent_ttl(e, &key);
+    }
+    payout_address
+}
+
+pub fn update_payout_address(e: &Env, token_id: u32, payout_address: &Option<Address>) {
+    let key = DataKey::PayoutAddress(token_id);
+    match payout_address {
+        Some(addr) => {
+            e.storage().persistent().set(&key, addr);
+            bump_persistent_ttl(e, &key);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+pub fn is_claim_delegate(e: &Env, owner: &Address, delegate: &Address) -> bool {
+    let key = DataKey::ClaimDelegate(owner.clone(), delegate.clone());
+    e.storage().persistent().get(&key).unwrap_or(false)
+}
+
+pub fn update_claim_delegate(e: &Env, owner: &Address, delegate: &Address, approved: bool) {
+    let key = DataKey::ClaimDelegate(owner.clone(), delegate.clone());
+    if approved {
+        e.storage().persistent().set(&key, &true);
+        bump_persistent_ttl(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub fn get_allowlist_config(e: &Env) -> AllowlistConfig {
+    e.storage()
+        .instance()
+        .get(&DataKey::AllowlistConfig)
+        .unwrap_or_default()
+}
+
+pub fn update_allowlist_config(e: &Env, config: &AllowlistConfig) {
+    e.storage()
+        .instance()
+        .set(&DataKey::AllowlistConfig, config);
+}
+
+pub fn is_allowlisted(e: &Env, addr: &Address) -> bool {
+    let key = DataKey::Allowlisted(addr.clone());
+    let allowlisted: bool = e.storage().persistent().get(&key).unwrap_or(false);
+    if allowlisted {
+        bump_persistent_ttl(e, &key);
+    }
+    allowlisted
+}
+
+pub fn update_allowlisted(e: &Env, addr: &Address, allowed: bool) {
+    let key = DataKey::Allowlisted(addr.clone());
+    if allowed {
+        e.storage().persistent().set(&key, &true);
+        bump_persistent_ttl(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub fn get_investor_total(e: &Env, addr: &Address) -> i128 {
+    let key = DataKey::InvestorTotal(addr.clone());
+    let total: i128 = e.storage().persistent().get(&key).unwrap_or(0);
+    if total > 0 {
+        bump_persistent_ttl(e, &key);
+    }
+    total
+}
+
+pub fn update_investor_total(e: &Env, addr: &Address, total: i128) {
+    let key = DataKey::InvestorTotal(addr.clone());
+    e.storage().persistent().set(&key, &total);
+    bump_persistent_ttl(e, &key);
+}
+
+pub fn get_investors_count(e: &Env) -> u32 {
+    e.storage()
+        .instance()
+        .get(&DataKey::InvestorsCount)
+        .unwrap_or(0)
+}
+
+pub fn update_investors_count(e: &Env, count: u32) {
+    e.storage().instance().set(&DataKey::InvestorsCount, &count);
+}
+
+pub fn get_compliance_officer(e: &Env) -> Option<Address> {
+    e.storage().instance().get(&DataKey::ComplianceOfficer)
+}
+
+pub fn update_compliance_officer(e: &Env, officer: &Option<Address>) {
+    match officer {
+        Some(addr) => e.storage().instance().set(&DataKey::ComplianceOfficer, addr),
+        None => e.storage().instance().remove(&DataKey::ComplianceOfficer),
+    }
+}
+
+pub fn is_position_frozen(e: &Env, token_id: u32) -> bool {
+    let key = DataKey::FrozenPosition(token_id);
+    let frozen: bool = e.storage().persistent().get(&key).unwrap_or(false);
+    if frozen {
+        bump_persistent_ttl(e, &key);
+    }
+    frozen
+}
+
+pub fn update_position_frozen(e: &Env, token_id: u32, frozen: bool) {
+    let key = DataKey::FrozenPosition(token_id);
+    if frozen {
+        e.storage().persistent().set(&key, &true);
+        bump_persistent_ttl(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub fn is_operation_paused(e: &Env, operation: Operation) -> bool {
+    e.storage()
+        .instance()
+        .get(&DataKey::OperationPaused(operation))
+        .unwrap_or(false)
+}
+
+pub fn update_operation_paused(e: &Env, operation: Operation, paused: bool) {
+    e.storage()
+        .instance()
+        .set(&DataKey::OperationPaused(operation), &paused);
+}
+
+pub fn get_pending_project_address(e: &Env) -> Option<PendingProjectAddress> {
+    e.storage().instance().get(&DataKey::PendingProjectAddress)
+}
+
+pub fn update_pending_project_address(e: &Env, pending: &Option<PendingProjectAddress>) {
+    match pending {
+        Some(pending) => e.storage().instance().set(&DataKey::PendingProjectAddress, pending),
+        None => e.storage().instance().remove(&DataKey::PendingProjectAddress),
+    }
+}
+
+pub fn get_tranche_raised(e: &Env, round_id: u32, tranche: u32) -> i128 {
+    e.storage()
+        .instance()
+        .get(&DataKey::TrancheRaised(round_id, tranche))
+        .unwrap_or(0)
+}
+
+pub fn update_tranche_raised(e: &Env, round_id: u32, tranche: u32, raised: i128) {
+    e.storage()
+        .instance()
+        .set(&DataKey::TrancheRaised(round_id, tranche), &raised);
+}
+
+/// Returns the arrears owed to the positions of `tranche`, kept up to date by `update_investment_with_claim`
+pub fn get_tranche_arrears(e: &Env, tranche: u32) -> i128 {
+    e.storage()
+        .instance()
+        .get(&DataKey::TrancheArrears(tranche))
+        .unwrap_or(0)
+}
+
+pub fn get_bonus_schedule(e: &Env) -> BonusSchedule {
+    e.storage()
+        .instance()
+        .get(&DataKey::BonusSchedule)
+        .unwrap_or_default()
+}
+
+pub fn update_bonus_schedule(e: &Env, schedule: &BonusSchedule) {
+    e.storage().instance().set(&DataKey::BonusSchedule, schedule);
+}
+
+pub fn get_referral_rewards(e: &Env, referrer: &Address) -> i128 {
+    let key = DataKey::ReferralRewards(referrer.clone());
+    let rewards: i128 = e.storage().persistent().get(&key).unwrap_or(0);
+    if rewards > 0 {
+        bump_persistent_ttl(e, &key);
+    }
+    rewards
+}
+
+pub fn update_referral_rewards(e: &Env, referrer: &Address, rewards: i128) {
+    let key = DataKey::ReferralRewards(referrer.clone());
+    if rewards > 0 {
+        e.storage().persistent().set(&key, &rewards);
+        bump_persistent_ttl(e, &key);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+pub fn get_distribution(e: &Env) -> Distribution {
+    e.storage()
+        .instance()
+        .get(&DataKey::Distribution)
+        .unwrap_or_default()
+}
+
+pub fn update_distribution(e: &Env, distribution: &Distribution) {
+    e.storage().instance().set(&DataKey::Distribution, distribution);
+}
+
+pub fn get_distribution_checkpoint(e: &Env, token_id: u32) -> i128 {
+    get_checkpoint(e, &DataKey::DistributionCheckpoint(token_id))
+}
+
+pub fn update_distribution_checkpoint(e: &Env, token_id: u32, checkpoint: i128) {
+    let key = DataKey::DistributionCheckpoint(token_id);
+    e.storage().persistent().set(&key, &checkpoint);
+    bump_persistent_ttl(e, &key);
+}
+
+pub fn get_revenue_pool(e: &Env) -> RevenuePool {
+    e.storage()
+        .instance()
+        .get(&DataKey::RevenuePool)
+        .unwrap_or_else(|| RevenuePool::new(e))
+}
+
+pub fn update_revenue_pool(e: &Env, pool: &RevenuePool) {
+    e.storage().instance().set(&DataKey::RevenuePool, pool);
+}
+
+pub fn get_revenue_checkpoint(e: &Env, token_id: u32) -> i128 {
+    get_checkpoint(e, &DataKey::RevenueCheckpoint(token_id))
+}
+
+pub fn update_revenue_checkpoint(e: &Env, token_id: u32, checkpoint: i128) {
+    let key = DataKey::RevenueCheckpoint(token_id);
+    e.storage().persistent().set(&key, &checkpoint);
+    bump_persistent_ttl(e, &key);
+}
+
+pub fn get_revenue_exit(e: &Env, token_id: u32) -> i128 {
+    let key = DataKey::RevenueExit(token_id);
+    let exit: i128 = e.storage().persistent().get(&key).unwrap_or(0);
+    if exit > 0 {
+        bump_persistent_ttl(e, &key);
+    }
+    exit
+}
+
+pub fn update_revenue_exit(e: &Env, token_id: u32, exit: i128) {
+    let key = DataKey::RevenueExit(token_id);
+    e.storage().persistent().set(&key, &exit);
+    bump_persistent_ttl(e, &key);
+}
+
+pub fn get_recovery_pool(e: &Env) -> RecoveryPool {
+    e.storage()
+        .instance()
+        .get(&DataKey::RecoveryPool)
+        .unwrap_or_else(|| RecoveryPool::new(e))
+}
+
+pub fn update_recovery_pool(e: &Env, pool: &RecoveryPool) {
+    e.storage().instance().set(&DataKey::RecoveryPool, pool);
+}
+
+pub fn get_recovery_claimed(e: &Env, token_id: u32) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&DataKey::RecoveryClaimed(token_id))
+        .unwrap_or(0)
+}
+
+pub fn update_recovery_claimed(e: &Env, token_id: u32, claimed: i128) {
+    let key = DataKey::RecoveryClaimed(token_id);
+    e.storage().persistent().set(&key, &claimed);
+    bump_persistent_ttl(e, &key);
+}
+
 fn bump_instance_ttl(e: &Env) {
     e.storage()
         .instance()
@@ -85,10 +385,21 @@ fn bump_persistent_ttl(e: &Env, key: &DataKey) {
         .extend_ttl(key, PERSISTENT_LIFETIME_THRESHOLD, PERSISTENT_BUMP_AMOUNT);
 }
 
+/// Reads an accumulator checkpoint, keeping it alive as long as its position is still claiming
+fn get_checkpoint(e: &Env, key: &DataKey) -> i128 {
+    match e.storage().persistent().get::<DataKey, i128>(key) {
+        Some(checkpoint) => {
+            bump_persistent_ttl(e, key);
+            checkpoint
+        }
+        None => 0,
+    }
+}
+
 fn set_investment(e: &Env, token_id: u32, investment: &Investment) {
     let key = DataKey::Investment(token_id);
     e.storage().persistent().set(&key, &investment);
-    if investment.status != InvestmentStatus::Finished {
+    if !investment.is_settled() {
         bump_persistent_ttl(e, &key);
     }
 }
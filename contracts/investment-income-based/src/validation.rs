@@ -1,9 +1,16 @@
-use crate::balance::ContractBalance;
-use crate::constants::SECONDS_IN_MONTH;
-use crate::data::{ContractData, State};
-use crate::investment::{Investment, InvestmentStatus};
+use crate::allowlist;
+use crate::balance::{ContractBalance, BPS_DENOMINATOR};
+use crate::bonus::BonusSchedule;
+use crate::constants::{
+    MAX_BONUS_BPS, MAX_FORECAST_PERIODS, MAX_KEEPER_BOUNTY_BPS, MAX_LATE_GRACE_DAYS, MAX_LATE_PENALTY_BPS,
+    SECONDS_IN_MONTH,
+};
+use crate::data::{ContractData, FromNumber, Operation, RoundParams, State, Tranche};
+use crate::investment::{Investment, InvestmentReturnType, InvestmentStatus};
+use crate::revenue::RevenuePool;
+use crate::storage as Storage;
 use soroban_sdk::token::TokenClient;
-use soroban_sdk::{contracterror, Address, Env};
+use soroban_sdk::{contracterror, Address, Env, Map, Vec};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
 #[repr(u32)]
@@ -27,6 +34,54 @@ pub enum Error {
     WouldExceedGoal = 30,
     GoalAlreadyReached = 31,
     AmountToInvestMustBeGreaterThanZero = 32,
+    OutstandingInvestmentsExist = 33,
+    TokenNotOwnedByClaimer = 34,
+    ClaimDelegateNotApproved = 35,
+    KeeperBountyTooHigh = 36,
+    AddressNotAllowlisted = 37,
+    AmountExceedsMaxPerInvestment = 38,
+    InvestorCapExceeded = 39,
+    MaxInvestorsReached = 40,
+    PositionFrozen = 41,
+    CallerNotComplianceOfficer = 42,
+    PositionNotFrozen = 43,
+    OperationPaused = 44,
+    ContractDefaulted = 45,
+    ContractNotDefaulted = 46,
+    AmountMustBeGreaterThanZero = 47,
+    NothingToRecover = 48,
+    LatePenaltyTooHigh = 49,
+    InvalidAutoTopUpCap = 50,
+    InvalidForecastRange = 51,
+    SurplusInsufficient = 52,
+    CannotRescueCampaignToken = 53,
+    ParamsLockedAfterInvestment = 54,
+    GoalCanOnlyBeRaised = 55,
+    ProjectAddressChangeNotReady = 56,
+    FundingClosed = 57,
+    RoundStillOpen = 58,
+    FundingNotStarted = 59,
+    NotEligibleForEarlyAccess = 60,
+    InvalidBonusSchedule = 61,
+    InvalidReferralShare = 62,
+    SelfReferral = 63,
+    NoReferralRewards = 64,
+    InvalidTranche = 65,
+    TrancheCapExceeded = 66,
+    InvalidRevenueCap = 67,
+    NotRevenueShare = 68,
+    NotScheduledReturn = 69,
+    RevenuePeriodAlreadyReported = 70,
+    NoRevenueSharePositions = 71,
+    NoRevenueToClaim = 72,
+    NoPositionsToDistribute = 73,
+    NoDistributionToClaim = 74,
+    InvalidPageLimit = 75,
+    InvalidInvestmentLimits = 76,
+    InvalidProjectAddress = 77,
+    SeniorArrearsOutstanding = 78,
+    ShortfallModeBatchOnly = 79,
+    LateGraceTooLong = 80,
 }
 
 /// Macro for validation checks with early return on error
@@ -62,9 +117,63 @@ pub fn validate_constructor_params(
     Ok(())
 }
 
+/// Validates that the investment caps are not negative and that a per-investment or per-investor cap
+/// (0 means no limit) leaves room for the minimum investment
+pub fn validate_investment_limits(
+    min_per_investment: i128,
+    max_per_investment: i128,
+    max_per_investor: i128,
+) -> Result<(), Error> {
+    require!(
+        max_per_investment == 0 || max_per_investment >= min_per_investment, Error::InvalidInvestmentLimits,
+        max_per_investor == 0 || max_per_investor >= min_per_investment, Error::InvalidInvestmentLimits
+    );
+    Ok(())
+}
+
+/// Validates updated campaign parameters with the same rules as the constructor
+pub fn validate_contract_params(contract_data: &ContractData) -> Result<(), Error> {
+    validate_constructor_params(
+        contract_data.interest_rate,
+        contract_data.goal,
+        contract_data.return_months,
+        contract_data.min_per_investment,
+    )?;
+    validate_investment_limits(
+        contract_data.min_per_investment,
+        contract_data.max_per_investment,
+        contract_data.max_per_investor,
+    )?;
+    validate_tranches(&contract_data.tranches, contract_data.goal)
+}
+
+/// Validates the terms of a new funding round with the same rules as the constructor. With tranches
+/// the rates come from the tranches, so the round can't set its own.
+pub fn validate_round_params(params: &RoundParams, tranches: &Vec<Tranche>) -> Result<InvestmentReturnType, Error> {
+    require!(
+        params.i_rate > 0 || !tranches.is_empty(), Error::InterestRateMustBeGreaterThanZero,
+        params.i_rate == 0 || tranches.is_empty(), Error::InvalidTranche,
+        params.goal > 0, Error::GoalMustBeGreaterThanZero,
+        params.return_months > 0, Error::ReturnMonthsMustBeGreaterThanZero,
+        params.min_per_investment > 0, Error::MinPerInvestmentMustBeGreaterThanZero
+    );
+    InvestmentReturnType::from_number(params.return_type).ok_or(Error::UnsupportedReturnType)
+}
+
+/// Validates that no investment has been received yet, so any parameter can still change
+pub fn validate_params_unlocked(received_so_far: i128) -> Result<(), Error> {
+    require!(
+        received_so_far == 0,
+        Error::ParamsLockedAfterInvestment
+    );
+    Ok(())
+}
+
 /// Validates that an investment is ready for payment processing
 pub fn validate_investment_payment(env: &Env, investment: &Investment) -> Result<(), Error> {
+    validate_position_not_frozen(env, investment.token_id)?;
     require!(
+        investment.return_type != InvestmentReturnType::RevenueShare, Error::NotScheduledReturn,
         env.ledger().timestamp() >= investment.claimable_ts, Error::AddressInvestmentIsNotClaimableYet,
         investment.status != InvestmentStatus::Finished, Error::AddressInvestmentIsFinished,
         investment.last_transfer_ts == 0 || (env.ledger().timestamp() - investment.last_transfer_ts) >= SECONDS_IN_MONTH, Error::AddressInvestmentNextTransferNotClaimableYet
@@ -92,6 +201,7 @@ pub fn validate_investment(
 ) -> Result<(), Error> {
     require!(
         amount >= contract_data.min_per_investment, Error::AmountLessThanMinimum,
+        contract_data.state != State::Defaulted, Error::ContractDefaulted,
         contract_data.state != State::FundsReached, Error::GoalAlreadyReached,
         investor_balance >= amount, Error::AddressInsufficientBalance,
         amount > 0, Error::AmountToInvestMustBeGreaterThanZero
@@ -99,6 +209,71 @@ pub fn validate_investment(
     Ok(())
 }
 
+/// Validates that funding has started, or that `addr` may invest within the early-access window
+/// (`early_access_secs` before `funding_start_ts`) because it is allowlisted or verified
+pub fn validate_funding_started(env: &Env, contract_data: &ContractData, addr: &Address) -> Result<(), Error> {
+    let now = env.ledger().timestamp();
+    if now >= contract_data.funding_start_ts {
+        return Ok(());
+    }
+
+    require!(
+        now >= contract_data.funding_start_ts.saturating_sub(contract_data.early_access_secs), Error::FundingNotStarted,
+        allowlist::is_listed(env, addr), Error::NotEligibleForEarlyAccess
+    );
+    Ok(())
+}
+
+/// Validates the per-investment, per-investor and per-campaign caps. A zero cap means no limit.
+pub fn validate_investment_caps(
+    amount: i128,
+    contract_data: &ContractData,
+    investor_total: i128,
+    investors_count: u32,
+) -> Result<(), Error> {
+    require!(
+        contract_data.max_per_investment == 0 || amount <= contract_data.max_per_investment, Error::AmountExceedsMaxPerInvestment,
+        contract_data.max_per_investor == 0 || investor_total + amount <= contract_data.max_per_investor, Error::InvestorCapExceeded,
+        contract_data.max_investors == 0 || investor_total > 0 || investors_count < contract_data.max_investors, Error::MaxInvestorsReached
+    );
+    Ok(())
+}
+
+/// Validates that every tranche has a positive interest rate and a non-negative cap
+pub fn validate_tranches(tranches: &Vec<Tranche>, goal: i128) -> Result<(), Error> {
+    let mut total_caps: i128 = 0;
+    for tranche in tranches.iter() {
+        require!(
+            tranche.i_rate > 0 && tranche.cap >= 0,
+            Error::InvalidTranche
+        );
+        total_caps = total_caps.checked_add(tranche.cap).ok_or(Error::InvalidTranche)?;
+    }
+    require!(total_caps <= goal, Error::InvalidTranche);
+    Ok(())
+}
+
+/// Validates that the chosen tranche exists and that the investment won't exceed its cap.
+/// Campaigns without tranches only accept tranche 0.
+pub fn validate_tranche_investment(
+    contract_data: &ContractData,
+    tranche: u32,
+    tranche_raised: i128,
+    amount_to_invest: i128,
+) -> Result<(), Error> {
+    if contract_data.tranches.is_empty() {
+        require!(tranche == 0, Error::InvalidTranche);
+        return Ok(());
+    }
+
+    let tranche = contract_data.tranches.get(tranche).ok_or(Error::InvalidTranche)?;
+    require!(
+        tranche.cap == 0 || tranche_raised + amount_to_invest <= tranche.cap,
+        Error::TrancheCapExceeded
+    );
+    Ok(())
+}
+
 /// Validates that investment won't exceed funding goal
 pub fn validate_investment_goal(
     received_so_far: i128,
@@ -145,9 +320,188 @@ pub fn validate_move_to_reserve(amount: i128, project_balance: i128) -> Result<(
 
 /// Validates that an investment is eligible for investor self-claim
 pub fn validate_claim(env: &Env, investment: &Investment) -> Result<(), Error> {
+    validate_position_not_frozen(env, investment.token_id)?;
+    require!(
+        investment.return_type != InvestmentReturnType::RevenueShare, Error::NotScheduledReturn,
+        env.ledger().timestamp() >= investment.claimable_ts, Error::AddressInvestmentIsNotClaimableYet,
+        !investment.is_settled(), Error::AddressInvestmentIsFinished
+    );
+    Ok(())
+}
+
+/// Validates that a revenue-share position can claim its share of the reported revenue
+pub fn validate_revenue_claim(env: &Env, investment: &Investment) -> Result<(), Error> {
+    validate_position_not_frozen(env, investment.token_id)?;
     require!(
+        investment.return_type == InvestmentReturnType::RevenueShare, Error::NotRevenueShare,
         env.ledger().timestamp() >= investment.claimable_ts, Error::AddressInvestmentIsNotClaimableYet,
         investment.status != InvestmentStatus::Finished, Error::AddressInvestmentIsFinished
     );
     Ok(())
 }
+
+/// Validates that revenue-share campaigns cap payouts at no less than the principal
+pub fn validate_revenue_cap(return_type: InvestmentReturnType, revenue_cap_bps: u32) -> Result<(), Error> {
+    require!(
+        return_type != InvestmentReturnType::RevenueShare || revenue_cap_bps as i128 >= BPS_DENOMINATOR,
+        Error::InvalidRevenueCap
+    );
+    Ok(())
+}
+
+/// Validates that a revenue report is for a new period and that some position shares it
+pub fn validate_revenue_report(pool: &RevenuePool, period: u32) -> Result<(), Error> {
+    require!(
+        period > pool.last_period, Error::RevenuePeriodAlreadyReported,
+        pool.total_weight > 0, Error::NoRevenueSharePositions
+    );
+    Ok(())
+}
+
+/// Validates that the keeper bounty stays within the allowed maximum
+pub fn validate_keeper_bounty(bounty_bps: u32) -> Result<(), Error> {
+    require!(
+        bounty_bps <= MAX_KEEPER_BOUNTY_BPS,
+        Error::KeeperBountyTooHigh
+    );
+    Ok(())
+}
+
+/// Validates that the referral share is at most the whole commission
+pub fn validate_referral_share(share_bps: u32) -> Result<(), Error> {
+    require!(
+        share_bps as i128 <= BPS_DENOMINATOR,
+        Error::InvalidReferralShare
+    );
+    Ok(())
+}
+
+/// Validates that investors don't refer themselves
+pub fn validate_referrer(investor: &Address, referrer: &Option<Address>) -> Result<(), Error> {
+    require!(
+        referrer.as_ref() != Some(investor),
+        Error::SelfReferral
+    );
+    Ok(())
+}
+
+/// Validates that the late-payment penalty rate stays within the allowed maximum
+pub fn validate_late_penalty(penalty_bps: u32, grace_days: u64) -> Result<(), Error> {
+    require!(
+        penalty_bps <= MAX_LATE_PENALTY_BPS, Error::LatePenaltyTooHigh,
+        grace_days <= MAX_LATE_GRACE_DAYS, Error::LateGraceTooLong
+    );
+    Ok(())
+}
+
+/// Validates that the bonuses of a schedule add up to at most `MAX_BONUS_BPS`, that its amounts are
+/// not negative and that a volume bonus has a threshold
+pub fn validate_bonus_schedule(schedule: &BonusSchedule) -> Result<(), Error> {
+    require!(
+        schedule
+            .early_bird_bps
+            .checked_add(schedule.volume_bonus_bps)
+            .is_some_and(|bonus| bonus <= MAX_BONUS_BPS)
+            && schedule.early_bird_raised >= 0
+            && schedule.volume_threshold >= 0
+            && (schedule.volume_bonus_bps == 0 || schedule.volume_threshold > 0),
+        Error::InvalidBonusSchedule
+    );
+    Ok(())
+}
+
+/// Validates that a forecast range is not empty and spans at most `MAX_FORECAST_PERIODS` periods
+pub fn validate_forecast_range(from_ts: u64, to_ts: u64) -> Result<(), Error> {
+    require!(
+        from_ts < to_ts && to_ts - from_ts <= MAX_FORECAST_PERIODS * SECONDS_IN_MONTH,
+        Error::InvalidForecastRange
+    );
+    Ok(())
+}
+
+/// Validates that an address is allowed to hold positions
+pub fn validate_allowlisted(env: &Env, addr: &Address) -> Result<(), Error> {
+    require!(
+        allowlist::is_allowed(env, addr),
+        Error::AddressNotAllowlisted
+    );
+    Ok(())
+}
+
+/// Validates that a position is not frozen
+pub fn validate_position_not_frozen(env: &Env, token_id: u32) -> Result<(), Error> {
+    require!(
+        !Storage::is_position_frozen(env, token_id),
+        Error::PositionFrozen
+    );
+    Ok(())
+}
+
+/// Validates that an operation group has not been paused
+pub fn validate_operation_not_paused(env: &Env, operation: Operation) -> Result<(), Error> {
+    require!(
+        !Storage::is_operation_paused(env, operation),
+        Error::OperationPaused
+    );
+    Ok(())
+}
+
+/// Validates that a new project address differs from the current one and from the contract itself
+pub fn validate_project_address(env: &Env, contract_data: &ContractData, project_address: &Address) -> Result<(), Error> {
+    require!(
+        *project_address != contract_data.project_address, Error::InvalidProjectAddress,
+        *project_address != env.current_contract_address(), Error::InvalidProjectAddress
+    );
+    Ok(())
+}
+
+/// Validates that no tranche more senior than `tranche` is owed arrears, so junior positions can't be
+/// paid from the reserve before the shortfall of senior ones has been settled
+pub fn validate_senior_arrears_settled(env: &Env, contract_data: &ContractData, tranche: u32) -> Result<(), Error> {
+    validate_senior_arrears_covered(env, contract_data, tranche, &Map::new(env))
+}
+
+/// Validates that every tranche more senior than `tranche` is owed no arrears beyond the amount
+/// `settled` for it, keyed by tranche index
+pub fn validate_senior_arrears_covered(
+    env: &Env,
+    contract_data: &ContractData,
+    tranche: u32,
+    settled: &Map<u32, i128>,
+) -> Result<(), Error> {
+    let seniority = contract_data.tranche_seniority(tranche);
+    for (senior_tranche, senior) in contract_data.tranches.iter().enumerate() {
+        let senior_tranche = senior_tranche as u32;
+        require!(
+            senior.seniority >= seniority
+                || Storage::get_tranche_arrears(env, senior_tranche) <= settled.get(senior_tranche).unwrap_or(0),
+            Error::SeniorArrearsOutstanding
+        );
+    }
+    Ok(())
+}
+
+/// Validates that single-position payouts are allowed: in shortfall mode the reserve is shared by
+/// `process_due_payments`, so a single position can't be paid in full ahead of the others
+pub fn validate_single_payout(contract_data: &ContractData) -> Result<(), Error> {
+    require!(!contract_data.shortfall_mode, Error::ShortfallModeBatchOnly);
+    Ok(())
+}
+
+/// Validates that the project has not been declared in default
+pub fn validate_not_defaulted(contract_data: &ContractData) -> Result<(), Error> {
+    require!(
+        contract_data.state != State::Defaulted,
+        Error::ContractDefaulted
+    );
+    Ok(())
+}
+
+/// Validates that the project has been declared in default
+pub fn validate_defaulted(contract_data: &ContractData) -> Result<(), Error> {
+    require!(
+        contract_data.state == State::Defaulted,
+        Error::ContractNotDefaulted
+    );
+    Ok(())
+}
@@ -8,7 +8,7 @@ pub use investment_income_based::{
 };
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
-    token, Address, Env, String,
+    token, Address, Env, String, Vec,
 };
 use token::Client as TokenClient;
 use token::StellarAssetClient as TokenAdminClient;
@@ -42,6 +42,30 @@ pub fn create_investment_contract(
     return_months: u32,
     min_per_investment: i128,
     mock_auths: bool,
+) -> TestData<'_> {
+    let investment_params: InvestmentContractParams = InvestmentContractParams {
+        i_rate,
+        claim_block_days,
+        goal,
+        return_type,
+        return_months,
+        min_per_investment,
+        max_per_investment: 0,
+        max_per_investor: 0,
+        max_investors: 0,
+        funding_start_ts: 0,
+        early_access_secs: 0,
+        tranches: Vec::new(e),
+        revenue_cap_bps: 0,
+    };
+
+    create_investment_contract_with_params(e, investment_params, mock_auths)
+}
+
+pub fn create_investment_contract_with_params(
+    e: &Env,
+    investment_params: InvestmentContractParams,
+    mock_auths: bool,
 ) -> TestData<'_> {
     if mock_auths {
         e.mock_all_auths();
@@ -54,15 +78,6 @@ pub fn create_investment_contract(
     let name = String::from_str(&e, "Test Token");
     let symbol = String::from_str(&e, "TT");
 
-    let investment_params: InvestmentContractParams = InvestmentContractParams {
-        i_rate,
-        claim_block_days,
-        goal,
-        return_type,
-        return_months,
-        min_per_investment,
-    };
-
     let client = InvestmentContractClient::new(
         e,
         &e.register(
@@ -89,13 +104,45 @@ pub fn create_investment_contract(
     }
 }
 
+pub fn default_investment_params(e: &Env) -> InvestmentContractParams {
+    InvestmentContractParams {
+        i_rate: 500,
+        claim_block_days: 7,
+        goal: 1000000,
+        return_type: 1,
+        return_months: 4,
+        min_per_investment: 100,
+        max_per_investment: 0,
+        max_per_investor: 0,
+        max_investors: 0,
+        funding_start_ts: 0,
+        early_access_secs: 0,
+        tranches: Vec::new(e),
+        revenue_cap_bps: 0,
+    }
+}
+
+/// Asserts the bookkeeping invariant: the contract always holds at least the tokens its balances account for
+pub fn assert_balances_reconciled(test_data: &TestData) {
+    let reconciliation = test_data.client.reconcile();
+    assert_eq!(
+        reconciliation.holdings,
+        test_data.token.balance(&test_data.client.address)
+    );
+    assert_eq!(reconciliation.deficit, 0);
+    assert_eq!(
+        reconciliation.holdings,
+        reconciliation.booked + reconciliation.surplus
+    );
+}
+
 pub fn do_mint_and_invest(e: &Env, test_data: &TestData) {
     let another_user: Address = Address::generate(e);
     test_data.token_admin.mint(&test_data.user, &1000000);
     test_data.token_admin.mint(&another_user, &1000000);
 
-    test_data.client.invest(&test_data.user, &100000);
-    test_data.client.invest(&another_user, &50000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.invest(&another_user, &50000, &None, &0);
 }
 
 pub fn do_test_investment(
@@ -153,6 +200,7 @@ pub fn do_process_investor_payment_test(
     token_id: u32,
 ) -> u64 {
     let investment_user_1: Investment = test_data.client.process_investor_payment(&token_id);
+    assert_balances_reconciled(test_data);
     assert_eq!(investment_user_1.status, status);
     assert!(investment_user_1.last_transfer_ts > *last_transfer_ts);
 
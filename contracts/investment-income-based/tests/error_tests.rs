@@ -1,6 +1,9 @@
 mod common;
 
-use common::{create_investment_contract, do_mint_and_invest};
+use common::{
+    create_investment_contract, create_investment_contract_with_params, default_investment_params,
+    do_mint_and_invest,
+};
 use soroban_sdk::Env;
 
 // ==================== Constructor Error Tests ====================
@@ -58,6 +61,39 @@ fn test_constructor_min_investment_zero() {
     create_investment_contract(&e, 500_u32, 7_u64, 1000000_i128, 1_u32, 4_u32, 0_i128, true);
 }
 
+#[test]
+#[should_panic(expected = "Error(Context, InvalidAction)")]
+fn test_constructor_max_per_investment_below_min() {
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_per_investment = params.min_per_investment - 1;
+    create_investment_contract_with_params(&e, params, true);
+}
+
+#[test]
+#[should_panic(expected = "Error(Context, InvalidAction)")]
+fn test_constructor_tranche_caps_above_goal() {
+    use investment_income_based::data::Tranche;
+    use soroban_sdk::vec;
+
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.tranches = vec![
+        &e,
+        Tranche {
+            i_rate: 300,
+            cap: params.goal,
+            seniority: 0,
+        },
+        Tranche {
+            i_rate: 900,
+            cap: 1,
+            seniority: 1,
+        },
+    ];
+    create_investment_contract_with_params(&e, params, true);
+}
+
 // ==================== Investment Error Tests ====================
 
 #[test]
@@ -68,8 +104,8 @@ fn test_goal_reached() {
         create_investment_contract(&e, 500_u32, 7_u64, 90000_i128, 2_u32, 4_u32, 100_i128, true);
 
     test_data.token_admin.mint(&test_data.user, &1000000);
-    test_data.client.invest(&test_data.user, &89000);
-    test_data.client.invest(&test_data.user, &2200);
+    test_data.client.invest(&test_data.user, &89000, &None, &0);
+    test_data.client.invest(&test_data.user, &2200, &None, &0);
 }
 
 #[test]
@@ -89,7 +125,7 @@ fn test_invest_insufficient_balance() {
 
     // Mint less tokens than needed so balance is insufficient
     test_data.token_admin.mint(&test_data.user, &50000);
-    test_data.client.invest(&test_data.user, &100000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
 }
 
 #[test]
@@ -109,7 +145,99 @@ fn test_invest_amount_less_than_minimum() {
 
     test_data.token_admin.mint(&test_data.user, &1000000);
     // Attempt to invest less than the minimum (min_per_investment = 100)
-    test_data.client.invest(&test_data.user, &50);
+    test_data.client.invest(&test_data.user, &50, &None, &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #37)")]
+fn test_invest_not_allowlisted() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_allowlist_enabled(&true);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #37)")]
+fn test_transfer_position_to_not_allowlisted() {
+    use soroban_sdk::{testutils::Address as _, vec, Address};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let buyer = Address::generate(&e);
+    test_data.client.set_allowlist_enabled(&true);
+    test_data
+        .client
+        .set_allowlisted(&vec![&e, test_data.user.clone()], &true);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    test_data
+        .client
+        .transfer(&test_data.user, &buyer, &investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #38)")]
+fn test_invest_above_max_per_investment() {
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_per_investment = 50000;
+    let test_data = create_investment_contract_with_params(&e, params, true);
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &50001, &None, &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #39)")]
+fn test_invest_above_max_per_investor() {
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_per_investor = 100000;
+    let test_data = create_investment_contract_with_params(&e, params, true);
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &60000, &None, &0);
+    test_data.client.invest(&test_data.user, &40001, &None, &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #40)")]
+fn test_invest_max_investors_reached() {
+    use soroban_sdk::{testutils::Address as _, Address};
+
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_investors = 1;
+    let test_data = create_investment_contract_with_params(&e, params, true);
+
+    let user2 = Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    test_data.client.invest(&test_data.user, &60000, &None, &0);
+    test_data.client.invest(&user2, &60000, &None, &0);
 }
 
 #[test]
@@ -132,7 +260,7 @@ fn test_invest_contract_paused() {
 
     // Attempt to invest with the contract paused
     test_data.token_admin.mint(&test_data.user, &1000000);
-    test_data.client.invest(&test_data.user, &100000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
 }
 
 // ==================== Payment Processing Error Tests ====================
@@ -173,7 +301,7 @@ fn test_process_payment_not_claimable_yet() {
     );
 
     test_data.token_admin.mint(&test_data.user, &1000000);
-    let investment = test_data.client.invest(&test_data.user, &100000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // Try to process payment before claimable_ts (do not advance ledger time)
     test_data
@@ -201,7 +329,7 @@ fn test_process_payment_investment_finished() {
 
     test_data.token_admin.mint(&test_data.user, &1000000);
     test_data.token_admin.mint(&test_data.admin, &1000000);
-    let investment = test_data.client.invest(&test_data.user, &100000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // Advance to claimable_ts and process payments until investment is finished
     e.ledger().set_timestamp(investment.claimable_ts);
@@ -246,7 +374,7 @@ fn test_process_payment_next_transfer_not_ready() {
 
     test_data.token_admin.mint(&test_data.user, &1000000);
     test_data.token_admin.mint(&test_data.admin, &1000000);
-    let investment = test_data.client.invest(&test_data.user, &100000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // Advance to claimable_ts
     e.ledger().set_timestamp(investment.claimable_ts);
@@ -265,6 +393,78 @@ fn test_process_payment_next_transfer_not_ready() {
         .process_investor_payment(&investment.token_id);
 }
 
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #41)")]
+fn test_claim_frozen_position() {
+    use soroban_sdk::testutils::Ledger;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data
+        .client
+        .freeze_position(&test_data.admin, &investment.token_id);
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.claim(&investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #42)")]
+fn test_freeze_position_not_compliance_officer() {
+    use soroban_sdk::{testutils::Address as _, Address};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    let stranger = Address::generate(&e);
+    test_data
+        .client
+        .freeze_position(&stranger, &investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #75)")]
+fn test_process_all_due_with_zero_limit() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    do_mint_and_invest(&e, &test_data);
+    test_data.client.process_all_due(&0, &0);
+}
+
 // ==================== Withdrawal Error Tests ====================
 
 #[test]
@@ -286,6 +486,30 @@ fn test_single_withdrawn_insufficient_balance() {
     test_data.client.single_withdrawn(&160000_i128);
 }
 
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #44)")]
+fn test_single_withdrawn_withdrawals_paused() {
+    use investment_income_based::data::Operation;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    do_mint_and_invest(&e, &test_data);
+    test_data
+        .client
+        .set_operation_paused(&Operation::Withdrawals, &true);
+    test_data.client.single_withdrawn(&10000_i128);
+}
+
 #[test]
 #[should_panic(expected = "HostError: Error(Contract, #24)")]
 fn test_move_funds_insufficient_project_balance() {
@@ -346,7 +570,7 @@ fn test_process_payment_insufficient_reserve() {
     );
 
     test_data.token_admin.mint(&test_data.user, &1000000);
-    let investment = test_data.client.invest(&test_data.user, &100000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // Advance to claimable_ts without adding funds to the reserve
     e.ledger().set_timestamp(investment.claimable_ts);
@@ -357,7 +581,742 @@ fn test_process_payment_insufficient_reserve() {
         .process_investor_payment(&investment.token_id);
 }
 
-// ==================== Lifecycle Error Tests ====================
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #2)")]
+fn test_process_due_payments_insufficient_reserve() {
+    use soroban_sdk::{testutils::Ledger, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    // The reserve covers one payment but not the whole batch
+    e.ledger().set_timestamp(investment1.claimable_ts);
+    let reserve = test_data.client.get_contract_balance().reserve;
+    test_data
+        .client
+        .move_funds_to_the_reserve(&(investment1.regular_payment - reserve));
+
+    test_data
+        .client
+        .process_due_payments(&vec![&e, investment1.token_id, investment2.token_id]);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #34)")]
+fn test_claim_many_positions_from_different_owners() {
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        vec, Address,
+    };
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let user2 = Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&user2, &100000, &None, &0);
+
+    e.ledger().set_timestamp(investment1.claimable_ts);
+    test_data
+        .client
+        .claim_many(&vec![&e, investment1.token_id, investment2.token_id]);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #35)")]
+fn test_claim_as_delegate_not_approved() {
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        Address,
+    };
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let bot = Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.claim_as_delegate(&bot, &investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #36)")]
+fn test_set_keeper_bounty_too_high() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_keeper_bounty(&5_000_u32);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #49)")]
+fn test_set_late_penalty_too_high() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_late_penalty(&5_000_u32, &5_u64);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #80)")]
+fn test_set_late_penalty_grace_too_long() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_late_penalty(&100_u32, &u64::MAX);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #2)")]
+fn test_auto_top_up_limited_by_cap() {
+    use soroban_sdk::testutils::Ledger;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    // The cap is too low to cover what the reserve is missing, so the payout still fails
+    test_data.client.set_auto_top_up(&true, &1000_i128);
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.claim(&investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #51)")]
+fn test_forecast_obligations_empty_range() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.forecast_obligations(&1000_u64, &1000_u64);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #52)")]
+fn test_sweep_surplus_above_surplus() {
+    use investment_income_based::data::SweepTarget;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    // Nothing was sent to the contract outside of its own operations
+    test_data
+        .client
+        .sweep_surplus(&1000, &SweepTarget::Owner);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #53)")]
+fn test_rescue_campaign_token() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    do_mint_and_invest(&e, &test_data);
+    test_data
+        .client
+        .rescue_token(&test_data.token.address, &test_data.admin, &1000);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #54)")]
+fn test_set_min_per_investment_after_investment() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    do_mint_and_invest(&e, &test_data);
+    test_data.client.set_min_per_investment(&1000);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #58)")]
+fn test_open_round_while_funding_is_open() {
+    use investment_income_based::data::RoundParams;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    do_mint_and_invest(&e, &test_data);
+    test_data.client.open_round(&RoundParams {
+        goal: 500000,
+        i_rate: 1000,
+        claim_block_days: 0,
+        return_type: 1,
+        return_months: 6,
+        min_per_investment: 1000,
+    });
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #59)")]
+fn test_invest_before_funding_start() {
+    use investment_income_based::data::InvestmentContractParams;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            funding_start_ts: 10000,
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #61)")]
+fn test_set_bonus_schedule_too_high() {
+    use investment_income_based::bonus::BonusSchedule;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_bonus_schedule(&BonusSchedule {
+        early_bird_bps: 800,
+        early_bird_raised: 100000,
+        early_bird_end_ts: 0,
+        volume_bonus_bps: 500,
+        volume_threshold: 200000,
+    });
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #61)")]
+fn test_set_bonus_schedule_overflowing_bonus() {
+    use investment_income_based::bonus::BonusSchedule;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    // The bonuses wrap around to a small value if added without overflow checks
+    test_data.client.set_bonus_schedule(&BonusSchedule {
+        early_bird_bps: u32::MAX,
+        early_bird_raised: 100000,
+        early_bird_end_ts: 0,
+        volume_bonus_bps: 2,
+        volume_threshold: 200000,
+    });
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #63)")]
+fn test_invest_with_self_referral() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_referral_share(&5_000_u32);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data
+        .client
+        .invest(&test_data.user, &100000, &Some(test_data.user.clone()), &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #66)")]
+fn test_invest_above_tranche_cap() {
+    use investment_income_based::data::{InvestmentContractParams, Tranche};
+    use soroban_sdk::vec;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            tranches: vec![
+                &e,
+                Tranche {
+                    i_rate: 300,
+                    cap: 50000,
+                    seniority: 0,
+                },
+            ],
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #65)")]
+fn test_open_round_with_rate_in_tranche_campaign() {
+    use investment_income_based::data::{InvestmentContractParams, RoundParams, Tranche};
+    use soroban_sdk::vec;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            tranches: vec![
+                &e,
+                Tranche {
+                    i_rate: 300,
+                    cap: 0,
+                    seniority: 0,
+                },
+            ],
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.close_funding();
+
+    // The tranche keeps its own rate, so the round can't set another one
+    test_data.client.open_round(&RoundParams {
+        goal: 500000,
+        i_rate: 1000,
+        claim_block_days: 0,
+        return_type: 1,
+        return_months: 6,
+        min_per_investment: 1000,
+    });
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #70)")]
+fn test_report_revenue_twice_for_same_period() {
+    use investment_income_based::data::InvestmentContractParams;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            return_type: 3,
+            revenue_cap_bps: 15_000,
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data
+        .token_admin
+        .mint(&test_data.project_address, &100000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    test_data.client.report_revenue(&1, &10000);
+    test_data.client.report_revenue(&1, &10000);
+}
+
+#[test]
+#[should_panic]
+fn test_report_revenue_contract_paused() {
+    use investment_income_based::data::InvestmentContractParams;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            return_type: 3,
+            revenue_cap_bps: 15_000,
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data
+        .token_admin
+        .mint(&test_data.project_address, &100000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    test_data.client.pause(&test_data.admin);
+    test_data.client.report_revenue(&1, &10000);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #74)")]
+fn test_claim_distribution_without_distributions() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.claim_distribution(&investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #77)")]
+fn test_set_project_address_to_current_address() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data
+        .client
+        .set_project_address(&test_data.project_address);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #78)")]
+fn test_junior_claim_while_senior_tranche_in_arrears() {
+    use investment_income_based::data::{InvestmentContractParams, Tranche};
+    use soroban_sdk::{testutils::Address as _, testutils::Ledger, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            tranches: vec![
+                &e,
+                Tranche {
+                    i_rate: 300,
+                    cap: 0,
+                    seniority: 0,
+                },
+                Tranche {
+                    i_rate: 900,
+                    cap: 0,
+                    seniority: 1,
+                },
+            ],
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    let junior_investor = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&junior_investor, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let senior = test_data.client.invest(&test_data.user, &300000, &None, &0);
+    let junior = test_data.client.invest(&junior_investor, &100000, &None, &1);
+
+    // The reserve only covers part of the senior installment, leaving it in arrears
+    test_data.client.set_shortfall_mode(&true);
+    e.ledger().set_timestamp(senior.claimable_ts);
+    test_data
+        .client
+        .process_due_payments(&vec![&e, senior.token_id]);
+
+    // Topping up the reserve doesn't let the junior position claim ahead of the senior arrears
+    test_data.client.add_company_transfer(&500000);
+    test_data.client.set_shortfall_mode(&false);
+    test_data.client.claim(&junior.token_id);
+}
+
+// ==================== Lifecycle Error Tests ====================
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #33)")]
+fn test_renounce_ownership_with_outstanding_investments() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    do_mint_and_invest(&e, &test_data);
+
+    // Investors still have pending payments, so ownership cannot be renounced
+    test_data.client.renounce_ownership();
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #46)")]
+fn test_claim_recovery_without_default() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.claim_recovery(&investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #45)")]
+fn test_claim_after_default() {
+    use soroban_sdk::testutils::Ledger;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.declare_default();
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.claim(&investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #45)")]
+fn test_project_withdrawal_after_default() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.declare_default();
+
+    // The project balance moved to the recovery pool at default can't be taken out nor moved to the reserve
+    assert!(test_data.client.try_move_funds_to_the_reserve(&100).is_err());
+    test_data.client.single_withdrawn(&100);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #79)")]
+fn test_shortfall_mode_rejects_single_payments() {
+    use soroban_sdk::testutils::Ledger;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.set_shortfall_mode(&true);
+
+    // Single payments would pay the installment in full ahead of the pro-rata batch
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data
+        .client
+        .process_investor_payment(&investment.token_id);
+}
+
+#[test]
+#[should_panic(expected = "HostError: Error(Contract, #79)")]
+fn test_self_claim_rejected_while_reserve_is_short() {
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&user2, &300000, &None, &0);
+    let reserve = test_data.client.get_contract_balance().reserve;
+    assert!(reserve < investment1.regular_payment + investment2.regular_payment);
+    test_data.client.set_shortfall_mode(&true);
+
+    // The first investor can't take a full installment out of the reserve shared with the second
+    e.ledger().set_timestamp(investment1.claimable_ts);
+    test_data.client.claim(&investment1.token_id);
+}
 
 // ==================== Authorization Tests ====================
 
@@ -1,6 +1,8 @@
 mod common;
 
-use common::{create_investment_contract, do_mint_and_invest, do_test_investment};
+use common::{
+    assert_balances_reconciled, create_investment_contract, do_mint_and_invest, do_test_investment,
+};
 use investment_income_based::balance::{calculate_rate_denominator, ContractBalance};
 use investment_income_based::investment::Investment;
 use soroban_sdk::{testutils::Ledger, Env};
@@ -52,7 +54,7 @@ fn test_investment_reverse_loan() {
         .mint(&test_data.project_address, &300000);
     test_data.token_admin.mint(&test_data.admin, &300000);
 
-    let investment_user: Investment = test_data.client.invest(&test_data.user, &100000);
+    let investment_user: Investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // Verify token balance at contract address
     assert!(test_data.token.balance(&test_data.client.address) > 0);
@@ -91,7 +93,7 @@ fn test_investment_coupon() {
         .mint(&test_data.project_address, &300000);
     test_data.token_admin.mint(&test_data.admin, &300000);
 
-    let investment_user: Investment = test_data.client.invest(&test_data.user, &100000);
+    let investment_user: Investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     let current_ts = e.ledger().timestamp();
     e.ledger().set_timestamp(current_ts + 604888);
@@ -147,6 +149,7 @@ fn test_single_withdrawn() {
         test_data.token.balance(&test_data.project_address),
         40000_i128
     );
+    assert_balances_reconciled(&test_data);
 }
 
 #[test]
@@ -199,6 +202,7 @@ fn test_move_funds_to_reserve() {
     let contract_balances: ContractBalance = test_data.client.get_contract_balance();
     assert!(contract_balances.reserve > 50000);
     assert!(contract_balances.project <= project_balance - 50000);
+    assert_balances_reconciled(&test_data);
 }
 
 #[test]
@@ -218,7 +222,7 @@ fn test_pause() {
     test_data.client.pause(&test_data.admin);
 
     test_data.token_admin.mint(&test_data.user, &1000000);
-    let invest_result = test_data.client.try_invest(&test_data.user, &100000);
+    let invest_result = test_data.client.try_invest(&test_data.user, &100000, &None, &0);
     assert!(invest_result.is_err());
 }
 
@@ -240,7 +244,7 @@ fn test_unpause() {
     test_data.client.unpause(&test_data.admin);
 
     test_data.token_admin.mint(&test_data.user, &1000000);
-    let investment = test_data.client.invest(&test_data.user, &100000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
     assert!(investment.deposited > 0);
 }
 
@@ -261,14 +265,14 @@ fn test_multiple_investments_same_user() {
     test_data.token_admin.mint(&test_data.user, &1000000);
     test_data.token_admin.mint(&test_data.admin, &600000);
 
-    let investment_1 = test_data.client.invest(&test_data.user, &100000);
+    let investment_1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
     let claimable_ts_1 = investment_1.claimable_ts;
     let deposited_1 = investment_1.deposited;
 
     let current_ts = e.ledger().timestamp();
     e.ledger().set_timestamp(current_ts + (8 * 24 * 60 * 60));
 
-    let investment_2 = test_data.client.invest(&test_data.user, &50000);
+    let investment_2 = test_data.client.invest(&test_data.user, &50000, &None, &0);
     let claimable_ts_2 = investment_2.claimable_ts;
     let deposited_2 = investment_2.deposited;
 
@@ -306,9 +310,9 @@ fn test_invest_at_goal_limit() {
 
     test_data.token_admin.mint(&test_data.user, &1000000);
 
-    test_data.client.invest(&test_data.user, &100000);
-    test_data.client.invest(&test_data.user, &50000);
-    test_data.client.invest(&test_data.user, &40000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.invest(&test_data.user, &50000, &None, &0);
+    test_data.client.invest(&test_data.user, &40000, &None, &0);
 
     let contract_balances = test_data.client.get_contract_balance();
 
@@ -317,7 +321,7 @@ fn test_invest_at_goal_limit() {
 
     // Si ya alcanzamos el goal, verificar que no se puede invertir más
     if contract_balances.received_so_far >= goal {
-        let result = test_data.client.try_invest(&test_data.user, &1000);
+        let result = test_data.client.try_invest(&test_data.user, &1000, &None, &0);
         assert!(
             result.is_err(),
             "Should not allow investment after reaching goal"
@@ -383,7 +387,7 @@ fn test_check_reserve_balance_no_claims_in_next_week() {
     );
 
     test_data.token_admin.mint(&test_data.user, &1000000);
-    let _investment = test_data.client.invest(&test_data.user, &100000);
+    let _investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // Don't advance time - claimable_ts is far in the future (7 days + more)
     // The claim won't be within the next week
@@ -412,7 +416,7 @@ fn test_check_reserve_balance_claim_in_next_week_sufficient() {
 
     // Get timestamp when investment is created
     let invest_timestamp = e.ledger().timestamp();
-    let _investment = test_data.client.invest(&test_data.user, &100000);
+    let _investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // next_transfer_ts = invest_timestamp + SECONDS_IN_MONTH (30 days)
     // Advance time to 29 days and 18 hours (within next week window from the payment date)
@@ -449,7 +453,7 @@ fn test_check_reserve_balance_claim_in_next_week_insufficient() {
 
     // Get timestamp when investment is created
     let invest_timestamp = e.ledger().timestamp();
-    let investment = test_data.client.invest(&test_data.user, &100000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
 
     // next_transfer_ts = invest_timestamp + SECONDS_IN_MONTH (30 days)
     // Advance time to 27 days (3 days before next payment, within next week window)
@@ -498,8 +502,8 @@ fn test_check_reserve_balance_multiple_claims_in_next_week() {
     let invest_timestamp = e.ledger().timestamp();
 
     // Both users invest
-    let investment1 = test_data.client.invest(&test_data.user, &100000);
-    let investment2 = test_data.client.invest(&user2, &50000);
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&user2, &50000, &None, &0);
 
     // Both next_transfer_ts will be invest_timestamp + SECONDS_IN_MONTH
     // Advance time to 28 days (2 days before next payment, within next week window)
@@ -564,3 +568,1546 @@ fn test_owner_authorization_verification() {
         "Authorization should be from admin/owner"
     );
 }
+
+#[test]
+fn test_two_step_ownership_transfer() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let new_owner = soroban_sdk::Address::generate(&e);
+    let live_until_ledger = e.ledger().sequence() + 1000;
+
+    test_data
+        .client
+        .transfer_ownership(&new_owner, &live_until_ledger);
+
+    // The transfer is pending until the new owner accepts it
+    assert_eq!(test_data.client.get_owner(), Some(test_data.admin.clone()));
+
+    test_data.client.accept_ownership();
+    assert_eq!(test_data.client.get_owner(), Some(new_owner.clone()));
+
+    // Company transfers are now funded by the new owner
+    test_data.token_admin.mint(&new_owner, &100000);
+    test_data.client.add_company_transfer(&100000_i128);
+
+    assert_eq!(test_data.token.balance(&new_owner), 0_i128);
+    assert_eq!(test_data.token.balance(&test_data.admin), 0_i128);
+
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.reserve_contributions, 100000_i128);
+}
+
+#[test]
+fn test_renounce_ownership_without_investments() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.renounce_ownership();
+    assert_eq!(test_data.client.get_owner(), None);
+}
+
+#[test]
+fn test_invest_emits_investment_created_event() {
+    use investment_income_based::events::InvestmentCreated;
+    use soroban_sdk::{testutils::Events as _, Event as _};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let events = e.events().all().filter_by_contract(&test_data.client.address);
+
+    // First investment: the contract balances hold exactly this investment's split
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    let expected = InvestmentCreated {
+        investor: test_data.user.clone(),
+        token_id: investment.token_id,
+        amount: 100000,
+        amount_to_invest: contract_balances.project,
+        amount_to_reserve_fund: contract_balances.reserve,
+        amount_to_commission: contract_balances.comission,
+        interest_rate: 500,
+    };
+
+    assert!(events
+        .events()
+        .contains(&expected.to_xdr(&e, &test_data.client.address)));
+}
+
+#[test]
+fn test_claim_emits_payment_processed_event() {
+    use investment_income_based::events::{PaymentProcessed, PaymentTrigger};
+    use soroban_sdk::{testutils::Events as _, Event as _};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+    test_data.client.claim(&investment.token_id);
+    let events = e.events().all().filter_by_contract(&test_data.client.address);
+
+    let expected = PaymentProcessed {
+        token_id: investment.token_id,
+        recipient: test_data.user.clone(),
+        amount: investment.regular_payment,
+        periods: 1,
+        trigger: PaymentTrigger::SelfClaim,
+    };
+
+    assert!(events
+        .events()
+        .contains(&expected.to_xdr(&e, &test_data.client.address)));
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_process_due_payments_batch() {
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&user2, &50000, &None, &0);
+
+    e.ledger().set_timestamp(investment1.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+
+    let user_balance = test_data.token.balance(&test_data.user);
+    let unknown_token_id = 99_u32;
+    let summary = test_data.client.process_due_payments(&vec![
+        &e,
+        investment1.token_id,
+        investment2.token_id,
+        unknown_token_id,
+    ]);
+
+    assert_eq!(summary.paid, vec![&e, investment1.token_id, investment2.token_id]);
+    assert_eq!(summary.skipped, vec![&e, unknown_token_id]);
+    assert_eq!(
+        summary.total_paid,
+        investment1.regular_payment + investment2.regular_payment
+    );
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + investment1.regular_payment
+    );
+
+    // Both investments were just paid, so a second batch skips them
+    let summary = test_data
+        .client
+        .process_due_payments(&vec![&e, investment1.token_id, investment2.token_id]);
+    assert!(summary.paid.is_empty());
+    assert_eq!(summary.total_paid, 0_i128);
+}
+
+#[test]
+fn test_process_all_due_with_cursor() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&user2, &50000, &None, &0);
+
+    e.ledger().set_timestamp(investment1.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+
+    let first_page = test_data.client.process_all_due(&0, &1);
+    assert_eq!(first_page.paid.len(), 1);
+    assert_eq!(first_page.next_cursor, Some(investment2.token_id));
+
+    let second_page = test_data
+        .client
+        .process_all_due(&first_page.next_cursor.unwrap(), &10);
+    assert_eq!(second_page.paid.len(), 1);
+    assert_eq!(second_page.next_cursor, None);
+
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert_eq!(
+        contract_balances.payments,
+        investment1.regular_payment + investment2.regular_payment
+    );
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_claim_many_and_claim_all() {
+    use soroban_sdk::vec;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&test_data.user, &50000, &None, &0);
+
+    e.ledger().set_timestamp(investment1.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+
+    let user_balance = test_data.token.balance(&test_data.user);
+    let investments = test_data
+        .client
+        .claim_many(&vec![&e, investment1.token_id, investment2.token_id]);
+    assert_eq!(investments.len(), 2);
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + investment1.regular_payment + investment2.regular_payment
+    );
+    assert_balances_reconciled(&test_data);
+
+    // Two months later both positions have two payments pending
+    let seconds_in_month = 30 * 24 * 60 * 60_u64;
+    e.ledger()
+        .set_timestamp(investment1.claimable_ts + 2 * seconds_in_month);
+
+    let user_balance = test_data.token.balance(&test_data.user);
+    let investments = test_data.client.claim_all(&test_data.user);
+    assert_eq!(investments.len(), 2);
+    for investment in investments.iter() {
+        assert_eq!(investment.payments_transferred, 3);
+    }
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + 2 * (investment1.regular_payment + investment2.regular_payment)
+    );
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_claim_to_payout_address_and_by_delegate() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let treasury = soroban_sdk::Address::generate(&e);
+    let bot = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data
+        .client
+        .set_payout_address(&investment.token_id, &Some(treasury.clone()));
+    assert_eq!(test_data.client.get_payout_address(&investment.token_id), treasury);
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+
+    test_data.client.claim(&investment.token_id);
+    assert_eq!(test_data.token.balance(&treasury), investment.regular_payment);
+
+    // An approved delegate claims on the owner's behalf, paying the treasury
+    test_data
+        .client
+        .set_claim_delegate(&test_data.user, &bot, &true);
+    assert!(test_data.client.is_claim_delegate(&test_data.user, &bot));
+
+    let seconds_in_month = 30 * 24 * 60 * 60_u64;
+    e.ledger()
+        .set_timestamp(investment.claimable_ts + seconds_in_month);
+    test_data.client.claim_as_delegate(&bot, &investment.token_id);
+    assert_eq!(
+        test_data.token.balance(&treasury),
+        2 * investment.regular_payment
+    );
+    assert_eq!(test_data.token.balance(&bot), 0_i128);
+
+    // Transferring the position resets its payout address
+    let buyer = soroban_sdk::Address::generate(&e);
+    test_data
+        .client
+        .transfer(&test_data.user, &buyer, &investment.token_id);
+    assert_eq!(test_data.client.get_payout_address(&investment.token_id), buyer);
+}
+
+#[test]
+fn test_poke_payment_rewards_keeper() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let keeper = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    test_data.client.set_keeper_bounty(&100_u32);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+
+    let commission_before = test_data.client.get_contract_balance().comission;
+    let user_balance = test_data.token.balance(&test_data.user);
+    let paid_investment = test_data.client.poke_payment(&keeper, &investment.token_id);
+
+    let expected_bounty = (investment.regular_payment * 100 / 10_000).min(commission_before);
+    assert_eq!(paid_investment.payments_transferred, 1);
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + investment.regular_payment
+    );
+    assert_eq!(test_data.token.balance(&keeper), expected_bounty);
+
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.keeper_bounties, expected_bounty);
+    assert_eq!(contract_balances.comission, commission_before - expected_bounty);
+    assert_balances_reconciled(&test_data);
+}
+
+mod verifier {
+    use soroban_sdk::{contract, contractimpl, Address, Env};
+
+    #[contract]
+    pub struct AlwaysVerified;
+
+    #[contractimpl]
+    impl AlwaysVerified {
+        pub fn is_verified(_env: Env, _investor: Address) -> bool {
+            true
+        }
+    }
+}
+
+#[test]
+fn test_allowlist_gates_investments_and_transfers() {
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let verified_user = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&verified_user, &1000000);
+
+    test_data.client.set_allowlist_enabled(&true);
+    assert!(!test_data.client.is_investor_allowed(&test_data.user));
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &100000, &None, &0)
+        .is_err());
+
+    test_data
+        .client
+        .set_allowlisted(&vec![&e, test_data.user.clone()], &true);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert!(investment.deposited > 0);
+
+    // Addresses outside the allowlist can be verified by an external contract
+    assert!(test_data
+        .client
+        .try_transfer(&test_data.user, &verified_user, &investment.token_id)
+        .is_err());
+
+    let verifier = e.register(verifier::AlwaysVerified, ());
+    test_data
+        .client
+        .set_investor_verifier(&Some(verifier.clone()));
+    assert_eq!(test_data.client.get_allowlist_config().verifier, Some(verifier));
+
+    test_data
+        .client
+        .transfer(&test_data.user, &verified_user, &investment.token_id);
+    assert_eq!(test_data.client.owner_of(&investment.token_id), verified_user);
+    test_data.client.invest(&verified_user, &100000, &None, &0);
+}
+
+#[test]
+fn test_investment_caps_within_limits() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_per_investment = 60000;
+    params.max_per_investor = 100000;
+    params.max_investors = 2;
+    let test_data = create_investment_contract_with_params(&e, params, true);
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+
+    // The same investor can add positions up to its cap without counting twice
+    test_data.client.invest(&test_data.user, &60000, &None, &0);
+    test_data.client.invest(&test_data.user, &40000, &None, &0);
+    test_data.client.invest(&user2, &50000, &None, &0);
+
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert!(contract_balances.received_so_far > 0);
+}
+
+#[test]
+fn test_freeze_seize_and_unfreeze_position() {
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let officer = soroban_sdk::Address::generate(&e);
+    let recovery = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    test_data.client.set_compliance_officer(&Some(officer.clone()));
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+
+    test_data
+        .client
+        .freeze_position(&officer, &investment.token_id);
+    assert!(test_data.client.is_position_frozen(&investment.token_id));
+    assert!(test_data.client.try_claim(&investment.token_id).is_err());
+
+    let summary = test_data
+        .client
+        .process_due_payments(&vec![&e, investment.token_id]);
+    assert_eq!(summary.skipped, vec![&e, investment.token_id]);
+
+    test_data
+        .client
+        .seize_position(&officer, &investment.token_id, &recovery);
+    assert_eq!(test_data.client.owner_of(&investment.token_id), recovery);
+
+    test_data
+        .client
+        .unfreeze_position(&test_data.admin, &investment.token_id);
+    test_data.client.claim(&investment.token_id);
+    assert_eq!(test_data.token.balance(&recovery), investment.regular_payment);
+}
+
+#[test]
+fn test_seize_position_moves_investor_total() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_per_investor = 100000;
+    let test_data = create_investment_contract_with_params(&e, params, true);
+
+    let recovery = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&recovery, &1000000);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data
+        .client
+        .freeze_position(&test_data.admin, &investment.token_id);
+    test_data
+        .client
+        .seize_position(&test_data.admin, &investment.token_id, &recovery);
+
+    // The seized principal no longer counts towards the cap of the previous holder...
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    // ...but towards the cap of the recovery address
+    assert!(test_data
+        .client
+        .try_invest(&recovery, &100, &None, &0)
+        .is_err());
+}
+
+#[test]
+fn test_transferred_position_counts_towards_receiver_cap() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let mut params = default_investment_params(&e);
+    params.max_per_investor = 100000;
+    let test_data = create_investment_contract_with_params(&e, params, true);
+
+    let receiver = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&receiver, &1000000);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data
+        .client
+        .transfer(&test_data.user, &receiver, &investment.token_id);
+
+    // The transferred principal no longer counts towards the cap of the sender...
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    // ...but towards the cap of the receiver
+    assert!(test_data
+        .client
+        .try_invest(&receiver, &100, &None, &0)
+        .is_err());
+}
+
+#[test]
+fn test_pause_investing_keeps_paying_investors() {
+    use investment_income_based::data::Operation;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    test_data
+        .client
+        .set_operation_paused(&Operation::Investing, &true);
+    assert!(test_data.client.is_operation_paused(&Operation::Investing));
+    assert!(!test_data.client.is_operation_paused(&Operation::Payouts));
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &100000, &None, &0)
+        .is_err());
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.add_company_transfer(&500000);
+    let claimed = test_data.client.claim(&investment.token_id);
+    assert_eq!(claimed.payments_transferred, 1);
+
+    test_data
+        .client
+        .set_operation_paused(&Operation::Investing, &false);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+}
+
+#[test]
+fn test_declare_default_and_recover_pro_rata() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let other_investor = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&other_investor, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &100000);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let other_investment = test_data.client.invest(&other_investor, &300000, &None, &0);
+    let balances = test_data.client.get_contract_balance();
+    test_data.client.single_withdrawn(&(balances.project - 20000));
+
+    // The reserve and the project funds not withdrawn yet go to the recovery pool
+    test_data.client.declare_default();
+    let pool = test_data.client.get_recovery_pool();
+    assert_eq!(pool.outstanding, investment.total + other_investment.total);
+    assert_eq!(pool.deposited, balances.reserve + 20000);
+    let contract_balances = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.reserve, 0);
+    assert_eq!(contract_balances.project, 0);
+    assert_balances_reconciled(&test_data);
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &100000, &None, &0)
+        .is_err());
+
+    test_data
+        .client
+        .deposit_recovery(&test_data.admin, &100000);
+    let deposited = pool.deposited + 100000;
+    let user_balance = test_data.token.balance(&test_data.user);
+
+    let recovered = test_data.client.claim_recovery(&investment.token_id);
+    assert_eq!(recovered, deposited * investment.total / pool.outstanding);
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + recovered
+    );
+    assert!(test_data
+        .client
+        .try_claim_recovery(&investment.token_id)
+        .is_err());
+
+    let other_recovered = test_data.client.claim_recovery(&other_investment.token_id);
+    assert_eq!(
+        other_recovered,
+        deposited * other_investment.total / pool.outstanding
+    );
+
+    let contract_balances = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.recovery_payments, recovered + other_recovered);
+    assert_eq!(contract_balances.recovery, deposited - recovered - other_recovered);
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_shortfall_mode_pays_pro_rata_and_settles_arrears() {
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let investment1 = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let investment2 = test_data.client.invest(&user2, &300000, &None, &0);
+    let total_due = investment1.regular_payment + investment2.regular_payment;
+    let reserve = test_data.client.get_contract_balance().reserve;
+    assert!(reserve < total_due);
+
+    test_data.client.set_shortfall_mode(&true);
+    e.ledger().set_timestamp(investment1.claimable_ts);
+
+    let user_balance = test_data.token.balance(&test_data.user);
+    let token_ids = vec![&e, investment1.token_id, investment2.token_id];
+    let summary = test_data.client.process_due_payments(&token_ids);
+
+    // Each installment receives the same fraction of the reserve
+    let paid1 = investment1.regular_payment * reserve / total_due;
+    let paid2 = investment2.regular_payment * reserve / total_due;
+    assert_eq!(summary.paid, token_ids);
+    assert_eq!(summary.total_paid, paid1 + paid2);
+    assert_eq!(test_data.token.balance(&test_data.user), user_balance + paid1);
+
+    // Once the reserve is topped up, the arrears are settled without waiting for the next installment
+    test_data.client.add_company_transfer(&500000);
+    let summary = test_data.client.process_due_payments(&token_ids);
+    assert_eq!(summary.total_paid, total_due - paid1 - paid2);
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + investment1.regular_payment
+    );
+
+    let summary = test_data.client.process_due_payments(&token_ids);
+    assert!(summary.paid.is_empty());
+}
+
+#[test]
+fn test_late_claim_with_funded_reserve_accrues_no_penalty() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    test_data.client.set_late_penalty(&100_u32, &5_u64);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    test_data.client.add_company_transfer(&500000);
+
+    // The investor waits well past the grace window, but the reserve could always cover the installments
+    let seconds_in_day = 86400_u64;
+    e.ledger()
+        .set_timestamp(investment.claimable_ts + 35 * seconds_in_day);
+
+    let user_balance = test_data.token.balance(&test_data.user);
+    let claimed = test_data.client.claim(&investment.token_id);
+
+    assert_eq!(claimed.payments_transferred, 2);
+    assert_eq!(claimed.penalty, 0);
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + 2 * investment.regular_payment
+    );
+    assert_eq!(test_data.client.get_contract_balance().penalties_paid, 0);
+}
+
+#[test]
+fn test_late_payment_penalty_accrues_on_arrears() {
+    use soroban_sdk::vec;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    test_data.client.set_late_penalty(&100_u32, &5_u64);
+    test_data.client.set_shortfall_mode(&true);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let reserve = test_data.client.get_contract_balance().reserve;
+    assert!(reserve < investment.regular_payment);
+
+    // The reserve only covers part of the first installment; the rest is kept as arrears
+    let token_ids = vec![&e, investment.token_id];
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.process_due_payments(&token_ids);
+    let arrears = investment.regular_payment - reserve;
+
+    // The arrears stay unpaid a full period past the 5-day grace window
+    let seconds_in_day = 86400_u64;
+    e.ledger()
+        .set_timestamp(investment.claimable_ts + 35 * seconds_in_day);
+    test_data.client.add_company_transfer(&500000);
+    test_data.client.process_due_payments(&token_ids);
+
+    let penalty = arrears * 100 / 10_000;
+    assert_eq!(test_data.client.get_contract_balance().penalties_paid, penalty);
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_auto_top_up_moves_missing_amount_from_project() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let balances_before = test_data.client.get_contract_balance();
+    assert!(balances_before.reserve < investment.regular_payment);
+
+    test_data.client.set_auto_top_up(&true, &0_i128);
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.claim(&investment.token_id);
+
+    let missing = investment.regular_payment - balances_before.reserve;
+    let contract_balances = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.reserve, 0);
+    assert_eq!(contract_balances.project, balances_before.project - missing);
+    assert_eq!(contract_balances.moved_from_project_to_reserve, missing);
+}
+
+#[test]
+fn test_forecast_obligations_counts_every_installment_and_coupon_principal() {
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        2_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+
+    // Six monthly buckets from the claimable date: four installments, then nothing
+    let seconds_in_month = 30 * 86400_u64;
+    let forecast = test_data.client.forecast_obligations(
+        &investment.claimable_ts,
+        &(investment.claimable_ts + 6 * seconds_in_month),
+    );
+
+    assert_eq!(forecast.buckets.len(), 6);
+    for i in 0..3 {
+        assert_eq!(
+            forecast.buckets.get(i).unwrap().amount,
+            investment.regular_payment
+        );
+    }
+    assert_eq!(
+        forecast.buckets.get(3).unwrap().amount,
+        investment.regular_payment + investment.deposited
+    );
+    assert_eq!(forecast.buckets.get(4).unwrap().amount, 0);
+
+    let total_due = 4 * investment.regular_payment + investment.deposited;
+    let reserve = test_data.client.get_contract_balance().reserve;
+    assert_eq!(forecast.total_due, total_due);
+    assert_eq!(forecast.reserve, reserve);
+    assert_eq!(forecast.coverage_bps, Some(reserve * 10_000 / total_due));
+}
+
+#[test]
+fn test_reconcile_and_sweep_surplus() {
+    use investment_income_based::data::SweepTarget;
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let keeper = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &1000000);
+    assert_balances_reconciled(&test_data);
+
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_balances_reconciled(&test_data);
+    test_data.client.add_company_transfer(&100000);
+    assert_balances_reconciled(&test_data);
+    test_data.client.move_funds_to_the_reserve(&10000);
+    assert_balances_reconciled(&test_data);
+    test_data.client.single_withdrawn(&10000);
+    assert_balances_reconciled(&test_data);
+
+    e.ledger().set_timestamp(investment.claimable_ts);
+    test_data.client.set_keeper_bounty(&100_u32);
+    test_data.client.poke_payment(&keeper, &investment.token_id);
+    assert_balances_reconciled(&test_data);
+
+    // Tokens sent straight to the contract show up as surplus and can be swept
+    let surplus_before = test_data.client.reconcile().surplus;
+    test_data
+        .token
+        .transfer(&test_data.admin, &test_data.client.address, &5000);
+    assert_eq!(test_data.client.reconcile().surplus, surplus_before + 5000);
+
+    let reserve = test_data.client.get_contract_balance().reserve;
+    test_data
+        .client
+        .sweep_surplus(&3000, &SweepTarget::Reserve);
+    assert_eq!(test_data.client.get_contract_balance().reserve, reserve + 3000);
+    assert_balances_reconciled(&test_data);
+
+    let admin_balance = test_data.token.balance(&test_data.admin);
+    test_data.client.sweep_surplus(&2000, &SweepTarget::Owner);
+    assert_eq!(test_data.token.balance(&test_data.admin), admin_balance + 2000);
+    assert_eq!(test_data.client.reconcile().surplus, surplus_before);
+
+    test_data.client.declare_default();
+    assert_balances_reconciled(&test_data);
+    test_data
+        .client
+        .deposit_recovery(&test_data.admin, &50000);
+    test_data.client.claim_recovery(&investment.token_id);
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_rescue_foreign_token() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let (foreign_token, foreign_token_admin) = common::create_token_contract(&e, &test_data.admin);
+    let recipient = soroban_sdk::Address::generate(&e);
+    foreign_token_admin.mint(&test_data.client.address, &7000);
+
+    test_data
+        .client
+        .rescue_token(&foreign_token.address, &recipient, &7000);
+    assert_eq!(foreign_token.balance(&recipient), 7000);
+    assert_eq!(foreign_token.balance(&test_data.client.address), 0);
+}
+
+#[test]
+fn test_update_campaign_parameters() {
+    use investment_income_based::events::ProjectAddressUpdated;
+    use soroban_sdk::{testutils::Address as _, testutils::Events as _, Event as _};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    // Before the first investment every campaign parameter can change immediately
+    test_data.client.set_goal(&95000);
+    test_data.client.set_min_per_investment(&1000);
+    test_data.client.set_claim_block_days(&0);
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    let investment = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_eq!(investment.claimable_ts, e.ledger().timestamp());
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &1000, &None, &0)
+        .is_err());
+
+    // Afterwards the goal can be raised, reopening the campaign
+    test_data.client.set_goal(&200000);
+    test_data.client.invest(&test_data.user, &1000, &None, &0);
+
+    // The project address only changes after the delay
+    let new_project_address = soroban_sdk::Address::generate(&e);
+    test_data.client.set_project_address(&new_project_address);
+    let pending = test_data.client.get_pending_project_address().unwrap();
+    assert!(test_data.client.try_apply_project_address().is_err());
+
+    e.ledger().set_timestamp(pending.effective_ts);
+    test_data.client.apply_project_address();
+    let expected = ProjectAddressUpdated {
+        project_address: new_project_address.clone(),
+        effective_ts: pending.effective_ts,
+    };
+    assert!(e
+        .events()
+        .all()
+        .filter_by_contract(&test_data.client.address)
+        .events()
+        .contains(&expected.to_xdr(&e, &test_data.client.address)));
+    assert!(test_data.client.get_pending_project_address().is_none());
+
+    test_data.client.single_withdrawn(&1000);
+    assert_eq!(test_data.token.balance(&new_project_address), 1000);
+    assert_eq!(test_data.token.balance(&test_data.project_address), 0);
+}
+
+#[test]
+fn test_close_funding_and_open_follow_on_round() {
+    use investment_income_based::data::RoundParams;
+    use investment_income_based::investment::{InvestmentReturnType, InvestmentStatus};
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+    let first = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_eq!(first.round_id, 1);
+
+    test_data.client.close_funding();
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &100000, &None, &0)
+        .is_err());
+
+    let round_id = test_data.client.open_round(&RoundParams {
+        goal: 500000,
+        i_rate: 1000,
+        claim_block_days: 0,
+        return_type: 2,
+        return_months: 6,
+        min_per_investment: 1000,
+    });
+    assert_eq!(round_id, 2);
+
+    let second = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_eq!(second.round_id, 2);
+    assert_eq!(second.return_type, InvestmentReturnType::Coupon);
+    assert_eq!(second.return_months, 6);
+    assert!(second.accumulated_interests > first.accumulated_interests);
+
+    // The first position keeps the terms of its round
+    test_data.client.add_company_transfer(&500000);
+    let seconds_in_month = 30 * 86400_u64;
+    e.ledger()
+        .set_timestamp(first.claimable_ts + 3 * seconds_in_month);
+    let claimed = test_data.client.claim(&first.token_id);
+    assert_eq!(claimed.payments_transferred, 4);
+    assert_eq!(claimed.status, InvestmentStatus::Finished);
+}
+
+#[test]
+fn test_funding_start_with_early_access() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use investment_income_based::data::InvestmentContractParams;
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    e.ledger().set_timestamp(1000);
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            funding_start_ts: 10000,
+            early_access_secs: 3000,
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    let early_investor = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&early_investor, &1000000);
+    test_data
+        .client
+        .set_allowlisted(&vec![&e, early_investor.clone()], &true);
+
+    // Nobody can invest before the early-access window
+    assert!(test_data
+        .client
+        .try_invest(&early_investor, &100000, &None, &0)
+        .is_err());
+
+    // Within the window only allowlisted addresses can, even with the allowlist disabled
+    e.ledger().set_timestamp(7000);
+    test_data.client.invest(&early_investor, &100000, &None, &0);
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &100000, &None, &0)
+        .is_err());
+
+    // Funding is open to everyone from the start time
+    e.ledger().set_timestamp(10000);
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+}
+
+#[test]
+fn test_bonus_schedule_sets_investment_rate() {
+    use investment_income_based::bonus::BonusSchedule;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_bonus_schedule(&BonusSchedule {
+        early_bird_bps: 200,
+        early_bird_raised: 50000,
+        early_bird_end_ts: 0,
+        volume_bonus_bps: 100,
+        volume_threshold: 200000,
+    });
+    test_data.token_admin.mint(&test_data.user, &1000000);
+
+    // Early bird: nothing raised yet
+    let early = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_eq!(early.interest_rate, 700);
+
+    // Large ticket once the early-bird amount has been raised
+    let large = test_data.client.invest(&test_data.user, &300000, &None, &0);
+    assert_eq!(large.interest_rate, 600);
+
+    // No bonus
+    let regular = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_eq!(regular.interest_rate, 500);
+    assert_eq!(regular.deposited, early.deposited);
+    assert!(early.accumulated_interests > regular.accumulated_interests);
+    assert!(early.regular_payment > regular.regular_payment);
+}
+
+#[test]
+fn test_referral_rewards_from_commission() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    test_data.client.set_referral_share(&5_000_u32);
+    let referrer = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+
+    // Without a referrer the whole commission goes to the platform
+    test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let commission = test_data.client.get_contract_balance().comission;
+    assert!(commission > 0);
+
+    // With a referrer, half of the commission of the same investment is credited to it
+    test_data
+        .client
+        .invest(&test_data.user, &100000, &Some(referrer.clone()), &0);
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.referrals, commission / 2);
+    assert_eq!(contract_balances.comission, commission + commission - commission / 2);
+    assert_eq!(test_data.client.get_referral_rewards(&referrer), commission / 2);
+    assert_balances_reconciled(&test_data);
+
+    let claimed = test_data.client.claim_referral_rewards(&referrer);
+    assert_eq!(claimed, commission / 2);
+    assert_eq!(test_data.token.balance(&referrer), commission / 2);
+    assert_eq!(test_data.client.get_referral_rewards(&referrer), 0);
+
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.referrals, 0);
+    assert_eq!(contract_balances.referrals_paid, commission / 2);
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_tranche_waterfall_in_shortfall_and_recovery() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use investment_income_based::data::{InvestmentContractParams, Tranche};
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            tranches: vec![
+                &e,
+                Tranche {
+                    i_rate: 300,
+                    cap: 0,
+                    seniority: 0,
+                },
+                Tranche {
+                    i_rate: 900,
+                    cap: 0,
+                    seniority: 1,
+                },
+            ],
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    let junior_investor = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&junior_investor, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let senior = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let junior = test_data.client.invest(&junior_investor, &300000, &None, &1);
+    assert_eq!(senior.tranche, 0);
+    assert_eq!(senior.interest_rate, 300);
+    assert_eq!(junior.tranche, 1);
+    assert_eq!(junior.interest_rate, 900);
+
+    // The reserve covers the senior installment but not the junior one
+    test_data.client.add_company_transfer(&30000);
+    let reserve = test_data.client.get_contract_balance().reserve;
+    assert!(reserve > senior.regular_payment);
+    assert!(reserve < senior.regular_payment + junior.regular_payment);
+
+    test_data.client.set_shortfall_mode(&true);
+    e.ledger().set_timestamp(senior.claimable_ts);
+    let user_balance = test_data.token.balance(&test_data.user);
+    let token_ids = vec![&e, senior.token_id, junior.token_id];
+    let summary = test_data.client.process_due_payments(&token_ids);
+
+    // The senior installment is paid in full and the junior one absorbs the shortfall
+    assert_eq!(summary.total_paid, reserve);
+    assert_eq!(
+        test_data.token.balance(&test_data.user),
+        user_balance + senior.regular_payment
+    );
+
+    // Recovered funds cover the senior tranche first
+    let project = test_data.client.get_contract_balance().project;
+    test_data.client.single_withdrawn(&project);
+    test_data.client.declare_default();
+    test_data
+        .client
+        .deposit_recovery(&test_data.admin, &50000);
+    assert_eq!(test_data.client.claim_recovery(&senior.token_id), 50000);
+    assert_balances_reconciled(&test_data);
+    assert!(test_data
+        .client
+        .try_claim_recovery(&junior.token_id)
+        .is_err());
+}
+
+#[test]
+fn test_batch_skips_junior_until_senior_arrears_are_settled() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use investment_income_based::data::{InvestmentContractParams, Tranche};
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            tranches: vec![
+                &e,
+                Tranche {
+                    i_rate: 300,
+                    cap: 0,
+                    seniority: 0,
+                },
+                Tranche {
+                    i_rate: 900,
+                    cap: 0,
+                    seniority: 1,
+                },
+            ],
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    let junior_investor = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&junior_investor, &1000000);
+    test_data.token_admin.mint(&test_data.admin, &500000);
+
+    let senior = test_data.client.invest(&test_data.user, &300000, &None, &0);
+    let junior = test_data.client.invest(&junior_investor, &100000, &None, &1);
+
+    // The reserve only covers part of the senior installment, leaving it in arrears
+    test_data.client.set_shortfall_mode(&true);
+    e.ledger().set_timestamp(senior.claimable_ts);
+    test_data
+        .client
+        .process_due_payments(&vec![&e, senior.token_id]);
+    test_data.client.add_company_transfer(&500000);
+
+    // A batch without the senior position can't pay the junior one ahead of the arrears
+    let summary = test_data
+        .client
+        .process_due_payments(&vec![&e, junior.token_id]);
+    assert!(summary.paid.is_empty());
+    assert_eq!(summary.skipped, vec![&e, junior.token_id]);
+
+    // A batch settling the senior arrears pays the junior position as well
+    let token_ids = vec![&e, senior.token_id, junior.token_id];
+    let summary = test_data.client.process_due_payments(&token_ids);
+    assert_eq!(summary.paid, token_ids);
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_tranche_caps_apply_per_round() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use investment_income_based::data::{InvestmentContractParams, RoundParams, Tranche};
+    use soroban_sdk::vec;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            tranches: vec![
+                &e,
+                Tranche {
+                    i_rate: 300,
+                    cap: 50000,
+                    seniority: 0,
+                },
+            ],
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.client.invest(&test_data.user, &50000, &None, &0);
+    assert!(test_data
+        .client
+        .try_invest(&test_data.user, &10000, &None, &0)
+        .is_err());
+
+    // A follow-on round starts with the tranche cap untouched and keeps the tranche rate
+    test_data.client.close_funding();
+    test_data.client.open_round(&RoundParams {
+        goal: 500000,
+        i_rate: 0,
+        claim_block_days: 0,
+        return_type: 1,
+        return_months: 4,
+        min_per_investment: 100,
+    });
+    let investment = test_data.client.invest(&test_data.user, &50000, &None, &0);
+    assert_eq!(investment.round_id, 2);
+    assert_eq!(investment.tranche, 0);
+    assert_eq!(investment.interest_rate, 300);
+}
+
+#[test]
+fn test_revenue_share_distribution_capped_at_multiple_of_principal() {
+    use common::{create_investment_contract_with_params, default_investment_params};
+    use investment_income_based::data::InvestmentContractParams;
+    use investment_income_based::investment::InvestmentStatus;
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract_with_params(
+        &e,
+        InvestmentContractParams {
+            return_type: 3,
+            revenue_cap_bps: 12_000,
+            claim_block_days: 0,
+            ..default_investment_params(&e)
+        },
+        true,
+    );
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    test_data
+        .token_admin
+        .mint(&test_data.project_address, &2000000);
+
+    let first = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    assert_eq!(first.regular_payment, 0);
+    assert_eq!(first.total, first.deposited * 12 / 10);
+
+    // The first report goes entirely to the only position
+    test_data.client.report_revenue(&1, &40000);
+    let claimed = test_data.client.claim_revenue(&first.token_id);
+    assert!((claimed.paid - 40000).abs() <= 1);
+    assert_eq!(claimed.status, InvestmentStatus::CashFlowing);
+    assert!(test_data
+        .client
+        .try_claim_revenue(&first.token_id)
+        .is_err());
+
+    // The first position reaches its cap within the next report, without claiming, and the rest of
+    // its share goes to the second position
+    let second = test_data.client.invest(&user2, &300000, &None, &0);
+    let first_left = claimed.total - claimed.paid;
+    let amount = first_left * (first.deposited + second.deposited) / first.deposited + 1000;
+    test_data.client.report_revenue(&2, &amount);
+    assert_eq!(
+        test_data.client.get_revenue_pool().total_weight,
+        second.deposited
+    );
+
+    let finished = test_data.client.claim_revenue(&first.token_id);
+    assert_eq!(finished.paid, finished.total);
+    assert_eq!(finished.status, InvestmentStatus::Finished);
+    let second_claimed = test_data.client.claim_revenue(&second.token_id);
+    assert!((second_claimed.paid - (amount - first_left)).abs() <= 2);
+
+    // Once every position has reached its cap, only what they could still earn is taken from the project
+    let project_tokens = test_data.token.balance(&test_data.project_address);
+    test_data.client.report_revenue(&3, &1000000);
+    let second_left = second_claimed.total - second_claimed.paid;
+    let taken = project_tokens - test_data.token.balance(&test_data.project_address);
+    assert!(taken >= second_left && taken <= second_left + 1);
+    assert_eq!(test_data.client.get_revenue_pool().total_weight, 0);
+    assert_eq!(
+        test_data.client.claim_revenue(&second.token_id).paid,
+        second.total
+    );
+    assert_balances_reconciled(&test_data);
+}
+
+#[test]
+fn test_distribution_claimed_pro_rata_by_deposited() {
+    use soroban_sdk::testutils::Address as _;
+
+    let e = Env::default();
+    let test_data = create_investment_contract(
+        &e,
+        500_u32,
+        7_u64,
+        1000000_i128,
+        1_u32,
+        4_u32,
+        100_i128,
+        true,
+    );
+
+    let user2 = soroban_sdk::Address::generate(&e);
+    test_data.token_admin.mint(&test_data.user, &1000000);
+    test_data.token_admin.mint(&user2, &1000000);
+    test_data
+        .token_admin
+        .mint(&test_data.project_address, &100000);
+
+    let first = test_data.client.invest(&test_data.user, &100000, &None, &0);
+    let second = test_data.client.invest(&user2, &300000, &None, &0);
+    let total_weight = first.deposited + second.deposited;
+    assert_eq!(test_data.client.get_distribution().total_weight, total_weight);
+
+    test_data.client.distribute(&40000);
+    let user_balance = test_data.token.balance(&test_data.user);
+    let claimed = test_data.client.claim_distribution(&first.token_id);
+    assert!((claimed - 40000 * first.deposited / total_weight).abs() <= 1);
+    assert_eq!(test_data.token.balance(&test_data.user), user_balance + claimed);
+    assert!(test_data
+        .client
+        .try_claim_distribution(&first.token_id)
+        .is_err());
+
+    // Positions claim independently and lazily
+    test_data.client.distribute(&20000);
+    let other_claimed = test_data.client.claim_distribution(&second.token_id);
+    assert!((other_claimed - 60000 * second.deposited / total_weight).abs() <= 1);
+
+    let distribution = test_data.client.get_distribution();
+    assert_eq!(distribution.distributed, 60000);
+    assert_eq!(distribution.claimed, claimed + other_claimed);
+    let contract_balances: ContractBalance = test_data.client.get_contract_balance();
+    assert_eq!(contract_balances.distributions_paid, claimed + other_claimed);
+    assert_balances_reconciled(&test_data);
+}